@@ -1,6 +1,10 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use rec::encryption::{self, DecryptionKey, RecipientKey};
+use rec::printer::{Commas, KeyOrder, Printer};
+use rec::units::{DurationUnit, SizeUnit};
+use rec::value::{DecimalFormat, EnumRepr, SerializeOptions, UnitVariantFormat};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(name = "rec")]
@@ -16,32 +20,320 @@ enum Commands {
     Validate {
         /// The REC file to validate
         file: PathBuf,
+        /// Key file to decrypt encrypted("..") values with
+        #[arg(long)]
+        key: Option<PathBuf>,
     },
     /// Convert REC to JSON
     ToJson {
         /// The REC file to convert
         file: PathBuf,
+        /// Write unit enum variants as their declared values instead of names
+        #[arg(long)]
+        enum_values: bool,
+        /// How enum variants are represented
+        #[arg(long, value_enum, default_value_t = EnumReprArg::Tagged)]
+        enum_repr: EnumReprArg,
+        /// Key holding the variant name for tagged, internal and adjacent representations
+        #[arg(long, default_value = "variant")]
+        enum_tag: String,
+        /// Key holding the variant data for tagged and adjacent representations
+        #[arg(long, default_value = "data")]
+        enum_content: String,
+        /// Write variant names as `Enum.Variant`
+        #[arg(long)]
+        enum_prefix: bool,
+        /// Write durations as numbers of this unit instead of text like "1h30m"
+        #[arg(long, value_enum)]
+        duration_unit: Option<DurationUnitArg>,
+        /// Write sizes as numbers of this unit instead of text like "512MiB"
+        #[arg(long, value_enum)]
+        size_unit: Option<SizeUnitArg>,
+        /// Write decimals as exact strings or as the nearest float
+        #[arg(long, value_enum, default_value_t = DecimalFormatArg::String)]
+        decimal_format: DecimalFormatArg,
+        /// Write secret("..") values as written instead of "[REDACTED]"
+        #[arg(long)]
+        reveal: bool,
+        /// Key file to decrypt encrypted("..") values with
+        #[arg(long)]
+        key: Option<PathBuf>,
     },
+    /// Format REC files in place
+    Fmt {
+        /// The REC files to format
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Report files that are not formatted instead of rewriting them
+        #[arg(long)]
+        check: bool,
+        /// Spaces per indentation level
+        #[arg(long, default_value_t = 4)]
+        indent: usize,
+        /// Where to put commas in multi-line blocks and lists
+        #[arg(long, value_enum, default_value_t = CommasArg::Lists)]
+        commas: CommasArg,
+        /// Sort object keys
+        #[arg(long)]
+        sort_keys: bool,
+    },
+    /// Create a key file for encrypted values and print its public key
+    Keygen {
+        /// Where to write the key file
+        #[arg(long, short)]
+        output: PathBuf,
+    },
+    /// Encrypt the secret("..") values of REC files in place
+    Encrypt {
+        /// The REC files to encrypt
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Public key to encrypt to, as printed by `rec keygen`
+        #[arg(long, required_unless_present = "key", conflicts_with = "key")]
+        recipient: Option<String>,
+        /// Key file whose public key to encrypt to
+        #[arg(long)]
+        key: Option<PathBuf>,
+    },
+    /// Decrypt the encrypted("..") values of REC files in place
+    Decrypt {
+        /// The REC files to decrypt
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Key file to decrypt with
+        #[arg(long)]
+        key: PathBuf,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum CommasArg {
+    Lists,
+    Trailing,
+    Never,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DecimalFormatArg {
+    String,
+    Number,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DurationUnitArg {
+    Ns,
+    Us,
+    Ms,
+    S,
+    M,
+    H,
+    D,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+#[value(rename_all = "verbatim")]
+enum SizeUnitArg {
+    B,
+    KB,
+    KiB,
+    MB,
+    MiB,
+    GB,
+    GiB,
+    TB,
+    TiB,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum EnumReprArg {
+    Tagged,
+    External,
+    Internal,
+    Adjacent,
+    Untagged,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Validate { file } => {
-            let content = fs::read_to_string(&file)?;
-            let doc = rec::parse_rec(&content)?;
+        Commands::Validate { file, key } => {
+            let doc = loader(key.as_deref())?.load(&file)?;
             rec::validate(&doc)?;
             println!("✓ {} is valid", file.display());
+            for derived in &doc.derived_addresses {
+                println!(
+                    "  {}: pda {} (bump {})",
+                    derived.path, derived.address, derived.bump
+                );
+            }
         }
-        Commands::ToJson { file } => {
-            let content = fs::read_to_string(&file)?;
-            let doc = rec::parse_rec(&content)?;
+        Commands::ToJson {
+            file,
+            enum_values,
+            enum_repr,
+            enum_tag,
+            enum_content,
+            enum_prefix,
+            duration_unit,
+            size_unit,
+            decimal_format,
+            reveal,
+            key,
+        } => {
+            let doc = loader(key.as_deref())?.load(&file)?;
             rec::validate(&doc)?;
-            let json = serde_json::to_string_pretty(&doc.root)?;
+            let options = SerializeOptions {
+                enum_repr: match enum_repr {
+                    EnumReprArg::Tagged => EnumRepr::Tagged {
+                        tag: enum_tag.into(),
+                        content: enum_content.into(),
+                    },
+                    EnumReprArg::External => EnumRepr::External,
+                    EnumReprArg::Internal => EnumRepr::Internal {
+                        tag: enum_tag.into(),
+                    },
+                    EnumReprArg::Adjacent => EnumRepr::Adjacent {
+                        tag: enum_tag.into(),
+                        content: enum_content.into(),
+                    },
+                    EnumReprArg::Untagged => EnumRepr::Untagged,
+                },
+                enum_prefix,
+                unit_variants: if enum_values {
+                    UnitVariantFormat::Value
+                } else {
+                    UnitVariantFormat::Name
+                },
+                duration_unit: duration_unit.map(|unit| match unit {
+                    DurationUnitArg::Ns => DurationUnit::Nanoseconds,
+                    DurationUnitArg::Us => DurationUnit::Microseconds,
+                    DurationUnitArg::Ms => DurationUnit::Milliseconds,
+                    DurationUnitArg::S => DurationUnit::Seconds,
+                    DurationUnitArg::M => DurationUnit::Minutes,
+                    DurationUnitArg::H => DurationUnit::Hours,
+                    DurationUnitArg::D => DurationUnit::Days,
+                }),
+                size_unit: size_unit.map(|unit| match unit {
+                    SizeUnitArg::B => SizeUnit::Bytes,
+                    SizeUnitArg::KB => SizeUnit::KB,
+                    SizeUnitArg::KiB => SizeUnit::KiB,
+                    SizeUnitArg::MB => SizeUnit::MB,
+                    SizeUnitArg::MiB => SizeUnit::MiB,
+                    SizeUnitArg::GB => SizeUnit::GB,
+                    SizeUnitArg::GiB => SizeUnit::GiB,
+                    SizeUnitArg::TB => SizeUnit::TB,
+                    SizeUnitArg::TiB => SizeUnit::TiB,
+                }),
+                decimal_format: match decimal_format {
+                    DecimalFormatArg::String => DecimalFormat::String,
+                    DecimalFormatArg::Number => DecimalFormat::Number,
+                },
+                reveal_secrets: reveal,
+            };
+            let json = serde_json::to_string_pretty(&doc.serialize_with(&options))?;
             println!("{}", json);
         }
+        Commands::Fmt {
+            files,
+            check,
+            indent,
+            commas,
+            sort_keys,
+        } => {
+            let printer = Printer {
+                indent: " ".repeat(indent),
+                commas: match commas {
+                    CommasArg::Lists => Commas::Lists,
+                    CommasArg::Trailing => Commas::Trailing,
+                    CommasArg::Never => Commas::Never,
+                },
+                key_order: if sort_keys {
+                    KeyOrder::Sorted
+                } else {
+                    KeyOrder::Preserve
+                },
+                ..Printer::new()
+            };
+            let mut unformatted = false;
+            for file in &files {
+                let content = fs::read_to_string(file)?;
+                let formatted = printer
+                    .format_str(&content)
+                    .map_err(|e| format!("{}: {}", file.display(), e))?;
+                if formatted == content {
+                    continue;
+                }
+                if check {
+                    println!("{} is not formatted", file.display());
+                    unformatted = true;
+                } else {
+                    fs::write(file, formatted)?;
+                    println!("Formatted {}", file.display());
+                }
+            }
+            if unformatted {
+                std::process::exit(1);
+            }
+        }
+        Commands::Keygen { output } => {
+            let key = DecryptionKey::generate()?;
+            write_key_file(&output, &key.to_file_contents())
+                .map_err(|e| format!("{}: {}", output.display(), e))?;
+            println!("{}", key.recipient());
+        }
+        Commands::Encrypt {
+            files,
+            recipient,
+            key,
+        } => {
+            let recipient: RecipientKey = match (recipient, key) {
+                (Some(recipient), _) => recipient.parse()?,
+                (None, Some(key)) => DecryptionKey::read(key)?.recipient(),
+                (None, None) => unreachable!("clap requires --recipient or --key"),
+            };
+            for file in &files {
+                let content = fs::read_to_string(file)?;
+                let (encrypted, count) = encryption::encrypt_source(&content, &recipient)
+                    .map_err(|e| format!("{}: {}", file.display(), e))?;
+                if count > 0 {
+                    fs::write(file, encrypted)?;
+                }
+                println!("Encrypted {} values in {}", count, file.display());
+            }
+        }
+        Commands::Decrypt { files, key } => {
+            let key = DecryptionKey::read(key)?;
+            for file in &files {
+                let content = fs::read_to_string(file)?;
+                let (decrypted, count) = encryption::decrypt_source(&content, &key)
+                    .map_err(|e| format!("{}: {}", file.display(), e))?;
+                if count > 0 {
+                    fs::write(file, decrypted)?;
+                }
+                println!("Decrypted {} values in {}", count, file.display());
+            }
+        }
     }
 
     Ok(())
 }
+
+fn loader(key: Option<&Path>) -> Result<rec::Loader, rec::RecError> {
+    let loader = rec::Loader::new();
+    match key {
+        Some(key) => Ok(loader.with_decryption_key(DecryptionKey::read(key)?)),
+        None => Ok(loader),
+    }
+}
+
+/// Creates the key file, readable only by its owner where the platform
+/// allows, and refuses to overwrite an existing one.
+fn write_key_file(path: &Path, contents: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(contents.as_bytes())
+}
@@ -1,18 +1,42 @@
+use crate::{Decimal, Pda, Pubkey, Secret};
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::fmt;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct RecDocument {
     pub includes: Vec<String>,
-    pub type_definitions: HashMap<String, TypeDef>,
-    pub enum_definitions: HashMap<String, EnumDef>,
+    /// `@type` definitions, in the order they were declared.
+    pub type_definitions: IndexMap<String, TypeDef>,
+    pub enum_definitions: IndexMap<String, EnumDef>,
+    /// `@alias Name = type` definitions. Field types that name an alias are
+    /// expanded when the document is parsed.
+    pub type_aliases: IndexMap<String, RecType>,
     pub root: RecObject,
+    /// The `pda(..)` values a [`Loader`](crate::Loader) derived, in document
+    /// order.
+    pub derived_addresses: Vec<DerivedAddress>,
+}
+
+/// A `pda(..)` value and the address it derived.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DerivedAddress {
+    /// The key path of the value, such as `accounts.vault`.
+    pub path: String,
+    pub address: Pubkey,
+    /// The bump seed appended to the seeds to land off the curve.
+    pub bump: u8,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct TypeDef {
     pub name: String,
+    /// The types composed into this one, via `extends` or `...Base`
+    /// spreads, in the order they were applied. A typed object of this type
+    /// is accepted wherever one of its bases is expected.
+    pub bases: Vec<String>,
+    /// Every field of the type, inherited and declared, in the order they
+    /// were composed.
     pub fields: IndexMap<String, FieldDef>,
 }
 
@@ -30,39 +54,322 @@ pub struct EnumDef {
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum EnumVariant {
-    Unit(String),
+    /// A unit variant, optionally with an explicit value (`INFO = 20`,
+    /// `UsEast = "us-east-1"`).
+    Unit(String, Option<EnumDiscriminant>),
     Tuple(String, Vec<RecType>),
     Struct(String, IndexMap<String, FieldDef>),
 }
 
+/// The explicit value assigned to a unit enum variant.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EnumDiscriminant {
+    Int(i64),
+    String(String),
+}
+
+impl EnumDef {
+    pub fn variant(&self, name: &str) -> Option<&EnumVariant> {
+        self.variants.iter().find(|v| v.name() == name)
+    }
+}
+
+impl EnumVariant {
+    pub fn name(&self) -> &str {
+        match self {
+            EnumVariant::Unit(name, _) => name,
+            EnumVariant::Tuple(name, _) => name,
+            EnumVariant::Struct(name, _) => name,
+        }
+    }
+
+    pub fn discriminant(&self) -> Option<&EnumDiscriminant> {
+        match self {
+            EnumVariant::Unit(_, value) => value.as_ref(),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum RecType {
     String,
     Int,
+    /// A fixed-width integer such as `u16`, range-checked by the validator.
+    SizedInt(IntType),
     Float,
+    /// An exact decimal number, optionally limited as in
+    /// `decimal(precision: 18, scale: 9)`.
+    Decimal(DecimalLimits),
     Bool,
-    Url,
+    Url(UrlPolicy),
+    /// An IP address and port, IPv4 or IPv6.
     Socket,
-    Pubkey,
+    Socket4,
+    Socket6,
+    /// A host name or IP address and port.
+    HostPort,
+    Ip,
+    Cidr,
+    Mac,
+    /// An ed25519 public key in base58. `Some(true)` requires a point on the
+    /// curve, as in `pubkey(on_curve: true)`; `Some(false)` requires one off
+    /// it, as program-derived addresses are.
+    Pubkey(Option<bool>),
+    /// A hex-encoded secp256k1 public key.
+    Secp256k1,
+    /// A 20-byte Ethereum address.
+    EvmAddress,
+    /// A `secret(..)` string.
+    Secret,
+    /// A byte string, in any encoding or only the given one, of any length
+    /// or exactly `len` bytes: `bytes`, `hex(32)`, `base64`.
+    Bytes(Option<ByteEncoding>, Option<usize>),
+    Duration,
+    /// A byte count.
+    Size,
+    /// An RFC 3339 `full-date`, such as `2026-01-01`.
+    Date(TimeBounds),
+    /// An RFC 3339 `partial-time`, such as `23:30:00`.
+    Time(TimeBounds),
+    /// An RFC 3339 `date-time`, such as `2026-01-01T00:00:00Z`.
+    DateTime(TimeBounds),
     Array(Box<RecType>),
-    Object(String), // named type
-    Enum(String),   // enum type
+    FixedArray(Box<RecType>, usize), // [T; N]
+    Tuple(Vec<RecType>),             // (A, B, ..)
+    Object(String),                  // named type
+    Enum(String),                    // enum type
     Any,
 }
 
+/// The fixed-width integer types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IntType {
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    I8,
+    I16,
+    I32,
+    I64,
+}
+
+impl IntType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "u8" => IntType::U8,
+            "u16" => IntType::U16,
+            "u32" => IntType::U32,
+            "u64" => IntType::U64,
+            "u128" => IntType::U128,
+            "i8" => IntType::I8,
+            "i16" => IntType::I16,
+            "i32" => IntType::I32,
+            "i64" => IntType::I64,
+            _ => return None,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            IntType::U8 => "u8",
+            IntType::U16 => "u16",
+            IntType::U32 => "u32",
+            IntType::U64 => "u64",
+            IntType::U128 => "u128",
+            IntType::I8 => "i8",
+            IntType::I16 => "i16",
+            IntType::I32 => "i32",
+            IntType::I64 => "i64",
+        }
+    }
+
+    pub fn min(self) -> i128 {
+        match self {
+            IntType::U8 | IntType::U16 | IntType::U32 | IntType::U64 | IntType::U128 => 0,
+            IntType::I8 => i8::MIN.into(),
+            IntType::I16 => i16::MIN.into(),
+            IntType::I32 => i32::MIN.into(),
+            IntType::I64 => i64::MIN.into(),
+        }
+    }
+
+    pub fn max(self) -> u128 {
+        match self {
+            IntType::U8 => u8::MAX.into(),
+            IntType::U16 => u16::MAX.into(),
+            IntType::U32 => u32::MAX.into(),
+            IntType::U64 => u64::MAX.into(),
+            IntType::U128 => u128::MAX,
+            IntType::I8 => i8::MAX as u128,
+            IntType::I16 => i16::MAX as u128,
+            IntType::I32 => i32::MAX as u128,
+            IntType::I64 => i64::MAX as u128,
+        }
+    }
+
+    pub fn bits(self) -> u32 {
+        match self {
+            IntType::U8 | IntType::I8 => 8,
+            IntType::U16 | IntType::I16 => 16,
+            IntType::U32 | IntType::I32 => 32,
+            IntType::U64 | IntType::I64 => 64,
+            IntType::U128 => 128,
+        }
+    }
+}
+
+/// The text encodings a byte string can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ByteEncoding {
+    Hex,
+    Base64,
+}
+
+/// SQL-style limits on a `decimal` field: at most `precision` digits in
+/// total, of which at most `scale` follow the decimal point.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecimalLimits {
+    pub precision: Option<u32>,
+    pub scale: Option<u32>,
+}
+
+impl fmt::Display for DecimalLimits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let limits: Vec<_> = [("precision", self.precision), ("scale", self.scale)]
+            .into_iter()
+            .filter_map(|(name, limit)| Some(format!("{}: {}", name, limit?)))
+            .collect();
+        if !limits.is_empty() {
+            write!(f, "({})", limits.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// What a `url` field accepts, written as
+/// `url(scheme: ["postgres", "postgresql"], host: true, path: false)` or, for
+/// schemes alone, `url<postgres, postgresql>`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UrlPolicy {
+    /// The allowed schemes. Empty means `http` and `https`.
+    pub schemes: Vec<String>,
+    /// `Some(true)` requires a host, `Some(false)` forbids one.
+    pub host: Option<bool>,
+    /// `Some(true)` requires a path other than `/`, `Some(false)` forbids
+    /// one.
+    pub path: Option<bool>,
+}
+
+impl UrlPolicy {
+    pub const DEFAULT_SCHEMES: [&'static str; 2] = ["http", "https"];
+
+    pub fn allows_scheme(&self, scheme: &str) -> bool {
+        if self.schemes.is_empty() {
+            Self::DEFAULT_SCHEMES.contains(&scheme)
+        } else {
+            self.schemes.iter().any(|s| s.eq_ignore_ascii_case(scheme))
+        }
+    }
+}
+
+impl fmt::Display for UrlPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut args = Vec::new();
+        if !self.schemes.is_empty() {
+            let schemes: Vec<_> = self.schemes.iter().map(|s| format!("\"{}\"", s)).collect();
+            args.push(format!("scheme: [{}]", schemes.join(", ")));
+        }
+        for (name, flag) in [("host", self.host), ("path", self.path)] {
+            if let Some(flag) = flag {
+                args.push(format!("{}: {}", name, flag));
+            }
+        }
+        if !args.is_empty() {
+            write!(f, "({})", args.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Exclusive bounds on a `date`, `time` or `datetime` field, written as
+/// `datetime(after: "..", before: "..")`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimeBounds {
+    pub after: Option<String>,
+    pub before: Option<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 pub enum RecValue {
     String(String),
     Int(i64),
+    /// An integer above `i64::MAX`. Integers that fit in an `i64` are always
+    /// `Int`.
+    UInt(u128),
     Float(f64),
+    Decimal(Decimal),
     Bool(bool),
     Null,
     Url(String),
     Socket(String),
+    HostPort(String),
+    Ip(String),
+    Cidr(String),
+    Mac(String),
     Pubkey(String),
+    /// A `pda(..)` value. `parse_rec` derives these into `Pubkey` values.
+    Pda(Pda),
+    /// The public half of a keypair file, written `keypair_pubkey("id.json")`.
+    /// A [`Loader`](crate::Loader) reads these into `Pubkey` values.
+    KeypairPubkey(String),
+    Secp256k1(String),
+    EvmAddress(String),
+    /// A string written `secret("..")`, redacted wherever it is printed or
+    /// serialized.
+    Secret(Secret),
+    /// A secret to read from a provider, written
+    /// `secret_ref("env", "JWT_SECRET")`. A [`Loader`](crate::Loader) reads
+    /// these into `Secret` values.
+    SecretRef {
+        provider: String,
+        name: String,
+    },
+    /// A secret encrypted to a [`RecipientKey`](crate::RecipientKey),
+    /// written `encrypted("..")` with the base64 payload. A
+    /// [`Loader`](crate::Loader) with a decryption key reads these into
+    /// `Secret` values.
+    Encrypted(String),
+    /// An environment variable, written `env("PORT")` or with a default,
+    /// `env("PORT", 8080)`. A [`Loader`](crate::Loader) reads these into
+    /// values of the field's type.
+    Env {
+        name: String,
+        default: Option<Box<RecValue>>,
+    },
+    /// A string literal with `${NAME}` references, as written, including any
+    /// `$${` escapes for a literal `${`. A [`Loader`](crate::Loader) fills
+    /// them in.
+    Template(String),
+    /// A byte string written as `hex("..")`.
+    Hex(String),
+    /// A byte string written as `base64("..")`.
+    Base64(String),
+    Duration(std::time::Duration),
+    /// A byte count, written with a unit as in `512MiB`.
+    Size(u64),
+    Date(String),
+    Time(String),
+    DateTime(String),
     Array(Vec<RecValue>),
+    Tuple(Vec<RecValue>),
     Object(RecObject),
     EnumVariant {
+        /// Empty for shorthand variants (`.INFO`, `INFO`) until they are
+        /// resolved against the type the value is assigned to.
         enum_name: String,
         variant: String,
         data: EnumVariantData,
@@ -77,6 +384,97 @@ pub enum EnumVariantData {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct RecObject {
+    /// The `@type` this object was written as (`ServerConfig { .. }`), if any.
+    #[serde(skip)]
+    pub type_name: Option<String>,
     pub fields: IndexMap<String, RecValue>,
 }
+
+impl RecObject {
+    pub fn new(fields: IndexMap<String, RecValue>) -> Self {
+        Self {
+            type_name: None,
+            fields,
+        }
+    }
+}
+
+impl fmt::Display for RecType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecType::String => write!(f, "string"),
+            RecType::Int => write!(f, "int"),
+            RecType::SizedInt(ty) => write!(f, "{}", ty.name()),
+            RecType::Float => write!(f, "float"),
+            RecType::Decimal(limits) => write!(f, "decimal{}", limits),
+            RecType::Bool => write!(f, "bool"),
+            RecType::Url(policy) => write!(f, "url{}", policy),
+            RecType::Socket => write!(f, "socket"),
+            RecType::Socket4 => write!(f, "socket4"),
+            RecType::Socket6 => write!(f, "socket6"),
+            RecType::HostPort => write!(f, "hostport"),
+            RecType::Ip => write!(f, "ip"),
+            RecType::Cidr => write!(f, "cidr"),
+            RecType::Mac => write!(f, "mac"),
+            RecType::Duration => write!(f, "duration"),
+            RecType::Size => write!(f, "size"),
+            RecType::Date(bounds) => write!(f, "date{}", bounds),
+            RecType::Time(bounds) => write!(f, "time{}", bounds),
+            RecType::DateTime(bounds) => write!(f, "datetime{}", bounds),
+            RecType::Pubkey(None) => write!(f, "pubkey"),
+            RecType::Pubkey(Some(on_curve)) => write!(f, "pubkey(on_curve: {})", on_curve),
+            RecType::Secp256k1 => write!(f, "secp256k1"),
+            RecType::EvmAddress => write!(f, "evm_address"),
+            RecType::Secret => write!(f, "secret"),
+            RecType::Bytes(encoding, len) => {
+                let name = match encoding {
+                    None => "bytes",
+                    Some(ByteEncoding::Hex) => "hex",
+                    Some(ByteEncoding::Base64) => "base64",
+                };
+                match len {
+                    Some(len) => write!(f, "{}({})", name, len),
+                    None => write!(f, "{}", name),
+                }
+            }
+            RecType::Array(inner) => write!(f, "[{}]", inner),
+            RecType::FixedArray(inner, len) => write!(f, "[{}; {}]", inner, len),
+            RecType::Tuple(types) => {
+                write!(f, "(")?;
+                for (i, ty) in types.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", ty)?;
+                }
+                write!(f, ")")
+            }
+            RecType::Object(name) | RecType::Enum(name) => write!(f, "{}", name),
+            RecType::Any => write!(f, "any"),
+        }
+    }
+}
+
+impl fmt::Display for TimeBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bounds: Vec<_> = [("after", &self.after), ("before", &self.before)]
+            .into_iter()
+            .filter_map(|(name, bound)| Some(format!("{}: \"{}\"", name, bound.as_ref()?)))
+            .collect();
+        if !bounds.is_empty() {
+            write!(f, "({})", bounds.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for EnumDiscriminant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnumDiscriminant::Int(i) => write!(f, "{}", i),
+            EnumDiscriminant::String(s) => write!(f, "\"{}\"", s),
+        }
+    }
+}
@@ -0,0 +1,604 @@
+//! A serde `Deserializer` that reads Rust types straight out of a
+//! [`RecValue`], without an intermediate JSON value.
+
+use crate::secret::SECRET_TOKEN;
+use crate::value::{DEFAULT_OPTIONS, EnumRepr, SerializeOptions};
+use crate::{EnumVariantData, RecError, RecValue, parse_rec, validate};
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor,
+    value::BorrowedStrDeserializer,
+};
+use serde::{Deserialize, Deserializer as _};
+use std::borrow::Cow;
+
+/// Parses and validates a REC document and deserializes its root object.
+pub fn from_str<T: DeserializeOwned>(input: &str) -> Result<T, RecError> {
+    let doc = parse_rec(input)?;
+    validate(&doc)?;
+    from_value(&RecValue::Object(doc.root))
+}
+
+pub fn from_value<'de, T: Deserialize<'de>>(value: &'de RecValue) -> Result<T, RecError> {
+    from_value_with(value, &DEFAULT_OPTIONS)
+}
+
+/// Deserializes `value`, presenting enum variants to serde's internally
+/// tagged, adjacently tagged and untagged enums in the layout chosen by
+/// `options`. Plain (externally tagged) Rust enums are matched on the
+/// variant directly and work with any options.
+pub fn from_value_with<'de, T: Deserialize<'de>>(
+    value: &'de RecValue,
+    options: &SerializeOptions,
+) -> Result<T, RecError> {
+    T::deserialize(Deserializer::new(value, String::new(), options))
+}
+
+impl<'de> de::Deserializer<'de> for &'de RecValue {
+    type Error = RecError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RecError> {
+        Deserializer::new(self, String::new(), &DEFAULT_OPTIONS).deserialize_any(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RecError> {
+        Deserializer::new(self, String::new(), &DEFAULT_OPTIONS).deserialize_option(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, RecError> {
+        Deserializer::new(self, String::new(), &DEFAULT_OPTIONS)
+            .deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, RecError> {
+        Deserializer::new(self, String::new(), &DEFAULT_OPTIONS)
+            .deserialize_enum(name, variants, visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+/// Deserializes one value, tracking its key path for error messages.
+pub struct Deserializer<'de, 'a> {
+    value: &'de RecValue,
+    path: String,
+    options: &'a SerializeOptions,
+}
+
+impl<'de, 'a> Deserializer<'de, 'a> {
+    fn new(value: &'de RecValue, path: String, options: &'a SerializeOptions) -> Self {
+        Self {
+            value,
+            path,
+            options,
+        }
+    }
+
+    fn visit<V: Visitor<'de>>(&self, visitor: V) -> Result<V::Value, RecError> {
+        match self.value {
+            RecValue::String(s)
+            | RecValue::Url(s)
+            | RecValue::Socket(s)
+            | RecValue::HostPort(s)
+            | RecValue::Ip(s)
+            | RecValue::Cidr(s)
+            | RecValue::Mac(s)
+            | RecValue::Date(s)
+            | RecValue::Time(s)
+            | RecValue::DateTime(s)
+            | RecValue::Hex(s)
+            | RecValue::Base64(s)
+            | RecValue::Pubkey(s)
+            | RecValue::Secp256k1(s)
+            | RecValue::EvmAddress(s) => visitor.visit_borrowed_str(s),
+            RecValue::Int(i) => visitor.visit_i64(*i),
+            RecValue::UInt(u) => match u64::try_from(*u) {
+                Ok(u) => visitor.visit_u64(u),
+                Err(_) => visitor.visit_u128(*u),
+            },
+            // In the shape serde gives `std::time::Duration`.
+            RecValue::Duration(d) => visitor.visit_map(de::value::MapDeserializer::new(
+                [("secs", d.as_secs()), ("nanos", d.subsec_nanos().into())].into_iter(),
+            )),
+            RecValue::Size(bytes) => visitor.visit_u64(*bytes),
+            RecValue::Float(f) => visitor.visit_f64(*f),
+            RecValue::Decimal(d) => visitor.visit_string(d.to_string()),
+            // Only `Secret` reads these, through `deserialize_newtype_struct`.
+            RecValue::Secret(_) | RecValue::SecretRef { .. } | RecValue::Encrypted(_) => Err(
+                de::Error::custom("secret values can only be read into rec::Secret"),
+            ),
+            RecValue::Env { .. } | RecValue::Template(_) => Err(self.value.unread_env_error()),
+            RecValue::Pda(_) | RecValue::KeypairPubkey(_) => {
+                visitor.visit_string(self.value.to_pubkey()?.to_string())
+            }
+            RecValue::Bool(b) => visitor.visit_bool(*b),
+            RecValue::Null => visitor.visit_unit(),
+            RecValue::Array(values) => visitor.visit_seq(SeqAccess::new(
+                values,
+                &self.path,
+                PathStyle::Index,
+                self.options,
+            )),
+            RecValue::Tuple(values) => visitor.visit_seq(SeqAccess::new(
+                values,
+                &self.path,
+                PathStyle::Field,
+                self.options,
+            )),
+            RecValue::Object(obj) => visitor.visit_map(MapAccess::new(
+                obj.fields
+                    .iter()
+                    .map(|(k, v)| (Cow::Borrowed(k.as_str()), Entry::Value(v))),
+                &self.path,
+                self.options,
+            )),
+            RecValue::EnumVariant {
+                enum_name,
+                variant,
+                data,
+            } => self.visit_variant(enum_name, variant, data, visitor),
+        }
+    }
+
+    fn decode_bytes(&self) -> Result<Vec<u8>, RecError> {
+        self.value
+            .to_bytes()
+            .map_err(|e| <RecError as de::Error>::custom(e).at(&self.path))
+    }
+
+    /// Presents an enum variant in the shape of `options.enum_repr`, for
+    /// types that inspect the data rather than asking for an enum.
+    fn visit_variant<V: Visitor<'de>>(
+        &self,
+        enum_name: &str,
+        variant: &'de str,
+        data: &'de EnumVariantData,
+        visitor: V,
+    ) -> Result<V::Value, RecError> {
+        let name = if self.options.enum_prefix {
+            format!("{}.{}", enum_name, variant)
+        } else {
+            variant.to_string()
+        };
+        let entries =
+            |tag: &'a str| std::iter::once((Cow::Borrowed(tag), Entry::Name(name.clone())));
+
+        match (&self.options.enum_repr, data) {
+            (EnumRepr::Tagged { .. }, EnumVariantData::Unit) => {
+                visitor.visit_string(format!("{}.{}", enum_name, variant))
+            }
+            (EnumRepr::External, EnumVariantData::Unit) => visitor.visit_string(name),
+            (EnumRepr::External, _) => visitor.visit_map(MapAccess::new(
+                std::iter::once((Cow::Owned(name.clone()), Entry::Data(data))),
+                &self.path,
+                self.options,
+            )),
+            (
+                EnumRepr::Internal { tag } | EnumRepr::Adjacent { tag, .. },
+                EnumVariantData::Unit,
+            ) => visitor.visit_map(MapAccess::new(entries(tag), &self.path, self.options)),
+            (EnumRepr::Internal { tag }, EnumVariantData::Struct(fields)) => {
+                visitor.visit_map(MapAccess::new(
+                    entries(tag).chain(
+                        fields
+                            .iter()
+                            .map(|(k, v)| (Cow::Borrowed(k.as_str()), Entry::Value(v))),
+                    ),
+                    &self.path,
+                    self.options,
+                ))
+            }
+            (EnumRepr::Internal { tag }, EnumVariantData::Tuple(values)) => match values.as_slice()
+            {
+                [RecValue::Object(obj)] => visitor.visit_map(MapAccess::new(
+                    entries(tag).chain(
+                        obj.fields
+                            .iter()
+                            .map(|(k, v)| (Cow::Borrowed(k.as_str()), Entry::Value(v))),
+                    ),
+                    &self.path,
+                    self.options,
+                )),
+                _ => Err(de::Error::custom(format!(
+                    "internally tagged enums cannot represent tuple variant {}.{}",
+                    enum_name, variant
+                ))),
+            },
+            (EnumRepr::Tagged { tag, content } | EnumRepr::Adjacent { tag, content }, _) => visitor
+                .visit_map(MapAccess::new(
+                    entries(tag).chain(std::iter::once((
+                        Cow::Borrowed(content.as_ref()),
+                        Entry::Data(data),
+                    ))),
+                    &self.path,
+                    self.options,
+                )),
+            (EnumRepr::Untagged, _) => {
+                DataDeserializer::new(data, &self.path, self.options).deserialize_any(visitor)
+            }
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de, '_> {
+    type Error = RecError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RecError> {
+        self.visit(visitor).map_err(|e| e.at(&self.path))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RecError> {
+        match self.value {
+            RecValue::Null => visitor.visit_none(),
+            _ => {
+                let path = self.path.clone();
+                visitor.visit_some(self).map_err(|e| e.at(&path))
+            }
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, RecError> {
+        let path = self.path.clone();
+        let result = match self.value {
+            RecValue::Secret(s) if name == SECRET_TOKEN => visitor.visit_str(s.expose_secret()),
+            RecValue::SecretRef { .. } | RecValue::Encrypted(_) if name == SECRET_TOKEN => self
+                .value
+                .to_secret()
+                .and_then(|s| visitor.visit_str(s.expose_secret())),
+            _ => visitor.visit_newtype_struct(self),
+        };
+        result.map_err(|e| e.at(&path))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, RecError> {
+        let result = match self.value {
+            RecValue::EnumVariant { variant, data, .. } => visitor.visit_enum(EnumAccess {
+                variant,
+                data,
+                path: &self.path,
+                options: self.options,
+            }),
+            RecValue::String(s) => visitor.visit_enum(BorrowedStrDeserializer::new(s)),
+            _ => self.visit(visitor),
+        };
+        result.map_err(|e| e.at(&self.path))
+    }
+
+    /// Decimals are strings to most types, but read as the nearest float
+    /// into `f32` and `f64`.
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RecError> {
+        match self.value {
+            RecValue::Decimal(d) => visitor.visit_f64(d.to_f64()),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RecError> {
+        self.deserialize_f64(visitor)
+    }
+
+    /// Byte strings are their text to most types, but decoded into
+    /// sequences (`Vec<u8>`, `[u8; N]`) and byte buffers.
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RecError> {
+        match self.value {
+            RecValue::Hex(_) | RecValue::Base64(_) => {
+                let bytes = self.decode_bytes()?;
+                visitor
+                    .visit_seq(de::value::SeqDeserializer::new(bytes.into_iter()))
+                    .map_err(|e: RecError| e.at(&self.path))
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, RecError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RecError> {
+        match self.value {
+            RecValue::Hex(_) | RecValue::Base64(_) => {
+                let bytes = self.decode_bytes()?;
+                visitor
+                    .visit_byte_buf(bytes)
+                    .map_err(|e: RecError| e.at(&self.path))
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RecError> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 char str string
+        unit unit_struct tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// How sequence elements extend the key path: `[i]` for arrays, `.i` for
+/// tuples.
+#[derive(Clone, Copy)]
+enum PathStyle {
+    Index,
+    Field,
+}
+
+struct SeqAccess<'de, 'a> {
+    values: std::slice::Iter<'de, RecValue>,
+    index: usize,
+    path: &'a str,
+    style: PathStyle,
+    options: &'a SerializeOptions,
+}
+
+impl<'de, 'a> SeqAccess<'de, 'a> {
+    fn new(
+        values: &'de [RecValue],
+        path: &'a str,
+        style: PathStyle,
+        options: &'a SerializeOptions,
+    ) -> Self {
+        Self {
+            values: values.iter(),
+            index: 0,
+            path,
+            style,
+            options,
+        }
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess<'de, '_> {
+    type Error = RecError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, RecError> {
+        let Some(value) = self.values.next() else {
+            return Ok(None);
+        };
+        let path = match self.style {
+            PathStyle::Index => format!("{}[{}]", self.path, self.index),
+            PathStyle::Field => join_path(self.path, &self.index.to_string()),
+        };
+        self.index += 1;
+        seed.deserialize(Deserializer::new(value, path, self.options))
+            .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.values.len())
+    }
+}
+
+/// What a map entry's value deserializes from.
+enum Entry<'de> {
+    Value(&'de RecValue),
+    /// A variant name under an enum tag key.
+    Name(String),
+    /// The data of an enum variant under its name or content key.
+    Data(&'de EnumVariantData),
+}
+
+struct MapAccess<'de, 'a, I> {
+    entries: I,
+    pending: Option<(Cow<'a, str>, Entry<'de>)>,
+    path: &'a str,
+    options: &'a SerializeOptions,
+}
+
+impl<'de, 'a, I> MapAccess<'de, 'a, I>
+where
+    I: Iterator<Item = (Cow<'a, str>, Entry<'de>)>,
+{
+    fn new(entries: I, path: &'a str, options: &'a SerializeOptions) -> Self {
+        Self {
+            entries,
+            pending: None,
+            path,
+            options,
+        }
+    }
+}
+
+impl<'de, 'a, I> de::MapAccess<'de> for MapAccess<'de, 'a, I>
+where
+    I: Iterator<Item = (Cow<'a, str>, Entry<'de>)>,
+{
+    type Error = RecError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, RecError> {
+        let Some((key, entry)) = self.entries.next() else {
+            return Ok(None);
+        };
+        let result = seed.deserialize(key.as_ref().into_deserializer());
+        self.pending = Some((key, entry));
+        result.map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, RecError> {
+        let (key, entry) = self
+            .pending
+            .take()
+            .ok_or_else(|| de::Error::custom("value requested before key"))?;
+        match entry {
+            Entry::Value(value) => {
+                let path = join_path(self.path, &key);
+                seed.deserialize(Deserializer::new(value, path, self.options))
+            }
+            Entry::Name(name) => seed.deserialize(name.into_deserializer()),
+            Entry::Data(data) => {
+                seed.deserialize(DataDeserializer::new(data, self.path, self.options))
+            }
+        }
+    }
+}
+
+/// Deserializes the data of an enum variant on its own: nothing for unit
+/// variants, the value itself for single-value tuple variants, a sequence
+/// for other tuple variants and a map for struct variants.
+struct DataDeserializer<'de, 'a> {
+    data: &'de EnumVariantData,
+    path: &'a str,
+    options: &'a SerializeOptions,
+}
+
+impl<'de, 'a> DataDeserializer<'de, 'a> {
+    fn new(data: &'de EnumVariantData, path: &'a str, options: &'a SerializeOptions) -> Self {
+        Self {
+            data,
+            path,
+            options,
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for DataDeserializer<'de, '_> {
+    type Error = RecError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RecError> {
+        let result = match self.data {
+            EnumVariantData::Unit => visitor.visit_unit(),
+            EnumVariantData::Tuple(values) => match values.as_slice() {
+                [value] => Deserializer::new(value, self.path.to_string(), self.options)
+                    .deserialize_any(visitor),
+                values => visitor.visit_seq(SeqAccess::new(
+                    values,
+                    self.path,
+                    PathStyle::Field,
+                    self.options,
+                )),
+            },
+            EnumVariantData::Struct(fields) => visitor.visit_map(MapAccess::new(
+                fields
+                    .iter()
+                    .map(|(k, v)| (Cow::Borrowed(k.as_str()), Entry::Value(v))),
+                self.path,
+                self.options,
+            )),
+        };
+        result.map_err(|e| e.at(self.path))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Maps a REC enum variant onto a Rust enum variant of the same name.
+struct EnumAccess<'de, 'a> {
+    variant: &'de str,
+    data: &'de EnumVariantData,
+    path: &'a str,
+    options: &'a SerializeOptions,
+}
+
+impl<'de, 'a> de::EnumAccess<'de> for EnumAccess<'de, 'a> {
+    type Error = RecError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), RecError> {
+        let variant = seed.deserialize(BorrowedStrDeserializer::new(self.variant))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for EnumAccess<'de, '_> {
+    type Error = RecError;
+
+    fn unit_variant(self) -> Result<(), RecError> {
+        match self.data {
+            EnumVariantData::Unit => Ok(()),
+            _ => Err(self.mismatch("unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, RecError> {
+        match self.data {
+            EnumVariantData::Tuple(values) if values.len() == 1 => seed.deserialize(
+                Deserializer::new(&values[0], self.path.to_string(), self.options),
+            ),
+            EnumVariantData::Struct(_) => {
+                seed.deserialize(DataDeserializer::new(self.data, self.path, self.options))
+            }
+            _ => Err(self.mismatch("newtype variant")),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, RecError> {
+        match self.data {
+            EnumVariantData::Tuple(values) if values.len() == len => visitor.visit_seq(
+                SeqAccess::new(values, self.path, PathStyle::Field, self.options),
+            ),
+            _ => Err(self.mismatch(&format!("tuple variant of {} values", len))),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, RecError> {
+        match self.data {
+            EnumVariantData::Struct(_) => {
+                DataDeserializer::new(self.data, self.path, self.options).deserialize_any(visitor)
+            }
+            _ => Err(self.mismatch("struct variant")),
+        }
+    }
+}
+
+impl EnumAccess<'_, '_> {
+    fn mismatch(&self, expected: &str) -> RecError {
+        let actual = match self.data {
+            EnumVariantData::Unit => "unit variant".to_string(),
+            EnumVariantData::Tuple(values) => format!("tuple variant of {} values", values.len()),
+            EnumVariantData::Struct(_) => "struct variant".to_string(),
+        };
+        de::Error::custom(format!(
+            "{} is a {}, expected a {}",
+            self.variant, actual, expected
+        ))
+    }
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
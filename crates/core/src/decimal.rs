@@ -0,0 +1,157 @@
+//! The exact decimal number behind `decimal("..")` and `1.25d` values.
+
+use crate::RecError;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// The newtype struct name `Decimal` serializes under, so `rec::to_value`
+/// can tell it from a plain string. Other serializers see the string.
+pub(crate) const DECIMAL_TOKEN: &str = "$rec::Decimal";
+
+/// A decimal number stored as an integer mantissa and a count of digits
+/// after the point: `1.250` is `1250` at scale 3. The scale is kept as
+/// written, so `1.250` and `1.25` compare unequal but display faithfully.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+impl Decimal {
+    /// The largest scale a `Decimal` can carry.
+    pub const MAX_SCALE: u32 = 38;
+
+    pub fn new(mantissa: i128, scale: u32) -> Result<Self, RecError> {
+        if scale > Self::MAX_SCALE {
+            return Err(RecError::InvalidDecimal(format!(
+                "scale {} is above the maximum of {}",
+                scale,
+                Self::MAX_SCALE
+            )));
+        }
+        Ok(Self { mantissa, scale })
+    }
+
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    /// The number of digits after the decimal point.
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// The number of digits before the decimal point, not counting leading
+    /// zeros.
+    pub fn integer_digits(&self) -> u32 {
+        let whole = self.mantissa.unsigned_abs() / 10u128.pow(self.scale);
+        if whole == 0 { 0 } else { whole.ilog10() + 1 }
+    }
+
+    /// Total digits as written in SQL terms: integer digits plus scale.
+    pub fn precision(&self) -> u32 {
+        self.integer_digits() + self.scale
+    }
+
+    /// The nearest `f64`. Exact only for values with about 15 significant
+    /// digits or fewer.
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = RecError;
+
+    fn from_str(s: &str) -> Result<Self, RecError> {
+        let invalid = || {
+            RecError::InvalidDecimal(format!(
+                "'{}' is not a decimal number; expected digits with an optional sign and \
+                 fraction, such as \"-12.50\"",
+                s
+            ))
+        };
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if whole.is_empty()
+            || !whole.bytes().all(|b| b.is_ascii_digit())
+            || !fraction.bytes().all(|b| b.is_ascii_digit())
+            || (digits.contains('.') && fraction.is_empty())
+        {
+            return Err(invalid());
+        }
+        let scale = fraction.len() as u32;
+        let magnitude: i128 = format!("{}{}", whole, fraction).parse().map_err(|_| {
+            RecError::InvalidDecimal(format!("'{}' has more than 38 significant digits", s))
+        })?;
+        Self::new(if negative { -magnitude } else { magnitude }, scale)
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Self {
+        Self {
+            mantissa: value.into(),
+            scale: 0,
+        }
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        if self.mantissa < 0 {
+            f.write_str("-")?;
+        }
+        if scale == 0 {
+            return f.write_str(&digits);
+        }
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - scale);
+        write!(f, "{}.{}", whole, fraction)
+    }
+}
+
+impl Serialize for Decimal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(DECIMAL_TOKEN, &self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DecimalVisitor;
+
+        impl Visitor<'_> for DecimalVisitor {
+            type Value = Decimal;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a decimal number or a string holding one")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Decimal, E> {
+                s.parse().map_err(E::custom)
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Decimal, E> {
+                Ok(v.into())
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Decimal, E> {
+                Decimal::new(v.into(), 0).map_err(E::custom)
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Decimal, E> {
+                v.to_string().parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(DecimalVisitor)
+    }
+}
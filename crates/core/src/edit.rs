@@ -0,0 +1,443 @@
+//! Format-preserving edits to `.rec` source.
+//!
+//! A [`DocumentMut`] wraps a [`syntax`] tree. Edits replace, add or remove
+//! only the nodes they touch, so everything else (comments, blank lines,
+//! commas and indentation) prints back exactly as it was read.
+//!
+//! ```rust,no_run
+//! use rec::RecValue;
+//! use rec::edit::{value, DocumentMut};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut doc: DocumentMut = std::fs::read_to_string("app.rec")?.parse()?;
+//! doc["server"]["port"] = value(8081);
+//! doc["server"]["allowed_origins"].push(value(RecValue::Url("https://b.example.com".into())))?;
+//! doc["cache"].remove("legacy_ttl");
+//! std::fs::write("app.rec", doc.to_string())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::parser;
+use crate::printer::{Printer, print_inline, write_key};
+use crate::syntax::{self, Element, Node, NodeKind, Token, TokenKind};
+use crate::{RecError, RecValue};
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentMut {
+    tree: Node,
+}
+
+impl DocumentMut {
+    pub fn parse(source: &str) -> Result<Self, RecError> {
+        Ok(Self {
+            tree: syntax::parse(source)?,
+        })
+    }
+
+    /// The root object.
+    pub fn root(&self) -> &Node {
+        self.tree
+            .node(NodeKind::Object)
+            .expect("a parsed document has a root object")
+    }
+
+    pub fn root_mut(&mut self) -> &mut Node {
+        self.tree
+            .node_mut(NodeKind::Object)
+            .expect("a parsed document has a root object")
+    }
+
+    /// The whole syntax tree, including directives and definitions.
+    pub fn as_tree(&self) -> &Node {
+        &self.tree
+    }
+}
+
+impl FromStr for DocumentMut {
+    type Err = RecError;
+
+    fn from_str(source: &str) -> Result<Self, RecError> {
+        Self::parse(source)
+    }
+}
+
+impl fmt::Display for DocumentMut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.tree)
+    }
+}
+
+impl Index<&str> for DocumentMut {
+    type Output = Node;
+
+    fn index(&self, key: &str) -> &Node {
+        &self.root()[key]
+    }
+}
+
+impl IndexMut<&str> for DocumentMut {
+    fn index_mut(&mut self, key: &str) -> &mut Node {
+        &mut self.root_mut()[key]
+    }
+}
+
+/// Builds a value node to assign into a document. Objects and arrays are
+/// written on one line.
+pub fn value(value: impl Into<RecValue>) -> Node {
+    syntax::parse_value(&print_inline(&value.into())).expect("printed values parse")
+}
+
+impl Node {
+    /// The object or array this node is, or that a value node holds.
+    fn group(&self, kind: NodeKind) -> Option<&Node> {
+        match self.kind {
+            k if k == kind => Some(self),
+            NodeKind::Value => self.node(kind),
+            _ => None,
+        }
+    }
+
+    fn group_mut(&mut self, kind: NodeKind) -> Option<&mut Node> {
+        match self.kind {
+            k if k == kind => Some(self),
+            NodeKind::Value => self.node_mut(kind),
+            _ => None,
+        }
+    }
+
+    /// The value stored under `key`, if this is an object.
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        self.group(NodeKind::Object)?
+            .nodes()
+            .find(|entry| entry.key().as_deref() == Some(key))?
+            .node(NodeKind::Value)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Node> {
+        self.group_mut(NodeKind::Object)?
+            .children
+            .iter_mut()
+            .find_map(|child| match child {
+                Element::Node(entry) if entry.key().as_deref() == Some(key) => {
+                    entry.node_mut(NodeKind::Value)
+                }
+                _ => None,
+            })
+    }
+
+    /// The item at `index`, if this is an array.
+    pub fn get_index(&self, index: usize) -> Option<&Node> {
+        self.group(NodeKind::Array)?.nodes().nth(index)
+    }
+
+    pub fn get_index_mut(&mut self, index: usize) -> Option<&mut Node> {
+        self.group_mut(NodeKind::Array)?
+            .children
+            .iter_mut()
+            .filter_map(|child| match child {
+                Element::Node(node) => Some(node),
+                Element::Token(_) => None,
+            })
+            .nth(index)
+    }
+
+    /// Sets `key` to `value`. An existing entry keeps its place and layout;
+    /// a new one is appended on its own line, indented like its siblings.
+    /// Returns the value that was replaced.
+    pub fn insert(&mut self, key: &str, value: Node) -> Result<Option<Node>, RecError> {
+        if let Some(existing) = self.get_mut(key) {
+            return Ok(Some(std::mem::replace(existing, value)));
+        }
+        let actual = self.to_string();
+        let object = self
+            .group_mut(NodeKind::Object)
+            .ok_or_else(|| not_a("object", actual))?;
+
+        let mut key_text = String::new();
+        write_key(&mut key_text, key);
+        let key_kind = if key_text.starts_with('"') {
+            TokenKind::String
+        } else {
+            TokenKind::Ident
+        };
+        let mut entry = Node::new(NodeKind::Entry);
+        entry.children = vec![
+            Element::Token(Token::new(key_kind, key_text)),
+            Element::Token(Token::new(TokenKind::Punct, ":")),
+            Element::Token(Token::new(TokenKind::Whitespace, " ")),
+            Element::Node(value),
+        ];
+        append(object, entry);
+        Ok(None)
+    }
+
+    /// Removes `key` and the line it was on, including comments directly
+    /// above it.
+    pub fn remove(&mut self, key: &str) -> Option<Node> {
+        let object = self.group_mut(NodeKind::Object)?;
+        let index = object.children.iter().position(
+            |child| matches!(child, Element::Node(entry) if entry.key().as_deref() == Some(key)),
+        )?;
+        let entry = remove_item(object, index);
+        entry.node(NodeKind::Value).cloned()
+    }
+
+    /// Appends an item to an array, on its own line if the array is
+    /// already laid out one item per line.
+    pub fn push(&mut self, value: Node) -> Result<(), RecError> {
+        let actual = self.to_string();
+        let array = self
+            .group_mut(NodeKind::Array)
+            .ok_or_else(|| not_a("array", actual))?;
+        append(array, value);
+        Ok(())
+    }
+
+    /// Removes the array item at `index`.
+    pub fn remove_index(&mut self, index: usize) -> Option<Node> {
+        let array = self.group_mut(NodeKind::Array)?;
+        let position = array
+            .children
+            .iter()
+            .enumerate()
+            .filter(|(_, child)| matches!(child, Element::Node(_)))
+            .nth(index)?
+            .0;
+        Some(remove_item(array, position))
+    }
+
+    /// The number of entries or items, if this is an object or array.
+    pub fn len(&self) -> usize {
+        self.group(NodeKind::Object)
+            .or_else(|| self.group(NodeKind::Array))
+            .map_or(0, |group| group.nodes().count())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Parses this node's text as a value. Shorthand enum variants are not
+    /// resolved, since the node carries no type information.
+    pub fn to_value(&self) -> Result<RecValue, RecError> {
+        parser::parse_value(&self.to_string())
+    }
+}
+
+fn not_a(expected: &str, actual: String) -> RecError {
+    RecError::TypeError {
+        expected: expected.to_string(),
+        actual,
+    }
+}
+
+impl Index<&str> for Node {
+    type Output = Node;
+
+    fn index(&self, key: &str) -> &Node {
+        self.get(key)
+            .unwrap_or_else(|| panic!("no entry '{}' in this object", key))
+    }
+}
+
+/// Indexing a missing key inserts it with a `null` value, ready to be
+/// assigned.
+impl IndexMut<&str> for Node {
+    fn index_mut(&mut self, key: &str) -> &mut Node {
+        if self.get(key).is_none() {
+            self.insert(key, value(RecValue::Null))
+                .unwrap_or_else(|e| panic!("cannot index with '{}': {}", key, e));
+        }
+        self.get_mut(key).expect("the entry was just inserted")
+    }
+}
+
+impl Index<usize> for Node {
+    type Output = Node;
+
+    fn index(&self, index: usize) -> &Node {
+        self.get_index(index)
+            .unwrap_or_else(|| panic!("no item {} in this array", index))
+    }
+}
+
+impl IndexMut<usize> for Node {
+    fn index_mut(&mut self, index: usize) -> &mut Node {
+        self.get_index_mut(index)
+            .unwrap_or_else(|| panic!("no item {} in this array", index))
+    }
+}
+
+fn is_token(child: &Element, kind: TokenKind) -> bool {
+    matches!(child, Element::Token(token) if token.kind == kind)
+}
+
+fn is_punct(child: &Element, punct: &str) -> bool {
+    matches!(child, Element::Token(token) if token.is_punct(punct))
+}
+
+fn token(kind: TokenKind, text: &str) -> Element {
+    Element::Token(Token::new(kind, text))
+}
+
+/// Adds `item` after the last item of a bracketed group, following the
+/// group's layout: on a new line with the same indentation when items are
+/// one per line, or after `, ` when they share a line. Commas are added
+/// only if the group already uses them.
+fn append(group: &mut Node, item: Node) {
+    let children = &mut group.children;
+    let close = children.len() - 1;
+    let newline = children.iter().find_map(|c| match c {
+        Element::Token(t) if t.kind == TokenKind::Newline => Some(t.text.clone()),
+        _ => None,
+    });
+    let Some(last) = children.iter().rposition(|c| matches!(c, Element::Node(_))) else {
+        // An empty group: `{}` becomes `{ item }`, `[]` becomes `[item]`, and
+        // a group already spanning lines gets the item on a line of its own.
+        let new = match newline {
+            Some(newline) => vec![
+                token(TokenKind::Newline, &newline),
+                token(TokenKind::Whitespace, &Printer::new().indent),
+                Element::Node(item),
+            ],
+            None if group.kind == NodeKind::Object => vec![
+                token(TokenKind::Whitespace, " "),
+                Element::Node(item),
+                token(TokenKind::Whitespace, " "),
+            ],
+            None => vec![Element::Node(item)],
+        };
+        children.splice(1..1, new);
+        return;
+    };
+    let newline = newline.unwrap_or_else(|| "\n".to_string());
+    let multiline = children[..last]
+        .iter()
+        .any(|c| is_token(c, TokenKind::Newline));
+    let uses_commas = children.iter().any(|c| is_punct(c, ","));
+
+    // The rest of the last item's line: a comma, spaces and a comment.
+    let mut line_end = last + 1;
+    let mut trailing_comma = false;
+    while line_end < close && !is_token(&children[line_end], TokenKind::Newline) {
+        trailing_comma |= is_punct(&children[line_end], ",");
+        line_end += 1;
+    }
+
+    if multiline {
+        let indent = match (&children[last - 1], &children[last - 2]) {
+            (Element::Token(ws), prev)
+                if ws.kind == TokenKind::Whitespace && is_token(prev, TokenKind::Newline) =>
+            {
+                Some(ws.text.clone())
+            }
+            _ => None,
+        };
+        let mut new = vec![token(TokenKind::Newline, &newline)];
+        if let Some(indent) = indent {
+            new.push(token(TokenKind::Whitespace, &indent));
+        }
+        new.push(Element::Node(item));
+        if trailing_comma {
+            new.push(token(TokenKind::Punct, ","));
+        } else if uses_commas {
+            children.insert(last + 1, token(TokenKind::Punct, ","));
+            line_end += 1;
+        }
+        children.splice(line_end..line_end, new);
+    } else if trailing_comma {
+        let comma = (last + 1..line_end)
+            .find(|&i| is_punct(&children[i], ","))
+            .expect("a trailing comma was seen");
+        let new = [
+            token(TokenKind::Whitespace, " "),
+            Element::Node(item),
+            token(TokenKind::Punct, ","),
+        ];
+        children.splice(comma + 1..comma + 1, new);
+    } else {
+        let new = [
+            token(TokenKind::Punct, ","),
+            token(TokenKind::Whitespace, " "),
+            Element::Node(item),
+        ];
+        children.splice(last + 1..last + 1, new);
+    }
+}
+
+/// Removes the item at `index` of a bracketed group. An item alone on its
+/// line takes the whole line with it, along with comment lines directly
+/// above; an item sharing a line takes its separating comma.
+fn remove_item(group: &mut Node, index: usize) -> Node {
+    let children = &mut group.children;
+    let close = children.len() - 1;
+
+    let mut line_start = index;
+    while line_start > 1 && is_token(&children[line_start - 1], TokenKind::Whitespace) {
+        line_start -= 1;
+    }
+    let mut line_end = index + 1;
+    while line_end < close && !is_token(&children[line_end], TokenKind::Newline) {
+        line_end += 1;
+    }
+    let alone = is_token(&children[line_start - 1], TokenKind::Newline)
+        && !children[index + 1..line_end]
+            .iter()
+            .any(|c| matches!(c, Element::Node(_)));
+
+    let range = if alone {
+        let mut start = line_start - 1;
+        // Comment lines directly above describe the item.
+        loop {
+            let mut k = start;
+            while k > 1 && is_token(&children[k - 1], TokenKind::Whitespace) {
+                k -= 1;
+            }
+            if k < 2 || !matches!(&children[k - 1], Element::Token(t) if t.kind.is_comment()) {
+                break;
+            }
+            let mut m = k - 1;
+            while m > 1 && is_token(&children[m - 1], TokenKind::Whitespace) {
+                m -= 1;
+            }
+            if !is_token(&children[m - 1], TokenKind::Newline) {
+                break;
+            }
+            start = m - 1;
+        }
+        start..line_end
+    } else {
+        let mut end = index + 1;
+        while end < close && is_token(&children[end], TokenKind::Whitespace) {
+            end += 1;
+        }
+        if is_punct(&children[end], ",") {
+            end += 1;
+            while end < close && is_token(&children[end], TokenKind::Whitespace) {
+                end += 1;
+            }
+            index..end
+        } else {
+            let mut start = index;
+            while start > 1 && is_token(&children[start - 1], TokenKind::Whitespace) {
+                start -= 1;
+            }
+            if is_punct(&children[start - 1], ",") {
+                start -= 1;
+            }
+            start..index + 1
+        }
+    };
+
+    let mut removed = children.drain(range);
+    removed
+        .find_map(|child| match child {
+            Element::Node(node) => Some(node),
+            Element::Token(_) => None,
+        })
+        .expect("the removed range holds the item")
+}
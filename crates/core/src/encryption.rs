@@ -0,0 +1,281 @@
+//! Encryption of individual values, written `encrypted("..")`.
+//!
+//! Each value is sealed to a [`RecipientKey`] with a fresh X25519 key pair:
+//! the shared secret goes through HKDF-SHA256 to give a ChaCha20-Poly1305
+//! key and nonce, so the ciphertext is authenticated and only the holder of
+//! the matching [`DecryptionKey`] can open it. The payload is base64 of a
+//! version byte, the ephemeral public key and the ciphertext.
+
+use crate::edit::value;
+use crate::syntax::{self, Element, Node, NodeKind, TokenKind};
+use crate::{RecError, RecValue, Secret};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use x25519_dalek::{PublicKey, StaticSecret};
+use zeroize::Zeroizing;
+
+const VERSION: u8 = 1;
+const INFO: &[u8] = b"rec encrypted value v1";
+
+/// The public half of a [`DecryptionKey`], written in base64. Values are
+/// encrypted to it.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct RecipientKey(PublicKey);
+
+impl RecipientKey {
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        self.0.as_bytes()
+    }
+}
+
+impl fmt::Display for RecipientKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&STANDARD.encode(self.0.as_bytes()))
+    }
+}
+
+impl fmt::Debug for RecipientKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RecipientKey({})", self)
+    }
+}
+
+impl FromStr for RecipientKey {
+    type Err = RecError;
+
+    fn from_str(s: &str) -> Result<Self, RecError> {
+        let bytes: [u8; 32] = STANDARD
+            .decode(s.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| {
+                RecError::Encryption(format!("'{}' is not a base64 X25519 public key", s))
+            })?;
+        Ok(Self(PublicKey::from(bytes)))
+    }
+}
+
+/// An X25519 secret key that opens values encrypted to its
+/// [`RecipientKey`].
+///
+/// Key files hold the secret key in base64 on one line. Blank lines and
+/// lines starting with `#` are ignored; [`DecryptionKey::to_file_contents`]
+/// writes the public key in such a comment.
+#[derive(Clone)]
+pub struct DecryptionKey(StaticSecret);
+
+impl DecryptionKey {
+    /// A new key from the operating system's random number generator.
+    pub fn generate() -> Result<Self, RecError> {
+        Ok(Self(StaticSecret::from(random_bytes()?)))
+    }
+
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(StaticSecret::from(bytes))
+    }
+
+    /// Reads a key file. Errors never include the key.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, RecError> {
+        let path = path.as_ref();
+        let text = Zeroizing::new(
+            std::fs::read_to_string(path)
+                .map_err(|e| RecError::Encryption(format!("{}: {}", path.display(), e)))?,
+        );
+        Self::from_file_contents(&text)
+            .map_err(|e| RecError::Encryption(format!("{}: {}", path.display(), e)))
+    }
+
+    fn from_file_contents(text: &str) -> Result<Self, String> {
+        let mut lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        let (Some(line), None) = (lines.next(), lines.next()) else {
+            return Err("expected one line holding a base64 X25519 secret key".to_string());
+        };
+        let bytes = Zeroizing::new(
+            STANDARD
+                .decode(line)
+                .map_err(|_| "the key is not valid base64".to_string())?,
+        );
+        let bytes: [u8; 32] = bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| format!("expected a 32-byte key, got {} bytes", bytes.len()))?;
+        Ok(Self::from_bytes(bytes))
+    }
+
+    /// The text of a key file holding this key.
+    pub fn to_file_contents(&self) -> Zeroizing<String> {
+        Zeroizing::new(format!(
+            "# public key: {}\n{}\n",
+            self.recipient(),
+            STANDARD.encode(self.0.as_bytes())
+        ))
+    }
+
+    pub fn recipient(&self) -> RecipientKey {
+        RecipientKey(PublicKey::from(&self.0))
+    }
+
+    /// Opens the payload of an `encrypted("..")` value.
+    pub fn decrypt(&self, payload: &str) -> Result<Secret, RecError> {
+        let invalid = || RecError::Encryption("the value is not an encrypted payload".to_string());
+        let bytes = STANDARD.decode(payload).map_err(|_| invalid())?;
+        let [version, rest @ ..] = bytes.as_slice() else {
+            return Err(invalid());
+        };
+        if *version != VERSION {
+            return Err(RecError::Encryption(format!(
+                "unsupported payload version {}",
+                version
+            )));
+        }
+        if rest.len() < 32 {
+            return Err(invalid());
+        }
+        let (ephemeral, ciphertext) = rest.split_at(32);
+        let ephemeral = PublicKey::from(<[u8; 32]>::try_from(ephemeral).unwrap());
+        let shared = self.0.diffie_hellman(&ephemeral);
+        let (cipher, nonce) = cipher(shared.as_bytes(), &ephemeral, &self.recipient());
+        let plaintext = Zeroizing::new(cipher.decrypt(&nonce, ciphertext).map_err(|_| {
+            RecError::Encryption(
+                "wrong key, or the value was modified after it was encrypted".to_string(),
+            )
+        })?);
+        let text = std::str::from_utf8(&plaintext)
+            .map_err(|_| RecError::Encryption("the value is not UTF-8 text".to_string()))?;
+        Ok(Secret::new(text))
+    }
+}
+
+impl fmt::Debug for DecryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DecryptionKey(recipient: {})", self.recipient())
+    }
+}
+
+/// Encrypts `plaintext` to `recipient`, giving the payload of an
+/// `encrypted("..")` value.
+pub fn encrypt(plaintext: &str, recipient: &RecipientKey) -> Result<String, RecError> {
+    let ephemeral_secret = StaticSecret::from(random_bytes()?);
+    let ephemeral = PublicKey::from(&ephemeral_secret);
+    let shared = ephemeral_secret.diffie_hellman(&recipient.0);
+    if !shared.was_contributory() {
+        return Err(RecError::Encryption(format!(
+            "{} is not a usable recipient key",
+            recipient
+        )));
+    }
+    let (cipher, nonce) = cipher(shared.as_bytes(), &ephemeral, recipient);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|_| RecError::Encryption("encryption failed".to_string()))?;
+    let mut payload = Vec::with_capacity(1 + 32 + ciphertext.len());
+    payload.push(VERSION);
+    payload.extend_from_slice(ephemeral.as_bytes());
+    payload.extend_from_slice(&ciphertext);
+    Ok(STANDARD.encode(payload))
+}
+
+/// The cipher and nonce for one value, expanded from the shared secret.
+/// Every value has its own ephemeral key, so a key and nonce are never used
+/// twice.
+fn cipher(
+    shared: &[u8; 32],
+    ephemeral: &PublicKey,
+    recipient: &RecipientKey,
+) -> (ChaCha20Poly1305, Nonce) {
+    let mut salt = [0u8; 64];
+    salt[..32].copy_from_slice(ephemeral.as_bytes());
+    salt[32..].copy_from_slice(recipient.as_bytes());
+    let mut okm = Zeroizing::new([0u8; 44]);
+    Hkdf::<Sha256>::new(Some(&salt), shared)
+        .expand(INFO, okm.as_mut())
+        .expect("44 bytes is a valid HKDF-SHA256 output length");
+    let key: [u8; 32] = okm[..32].try_into().unwrap();
+    let nonce: [u8; 12] = okm[32..].try_into().unwrap();
+    (ChaCha20Poly1305::new(&Key::from(key)), Nonce::from(nonce))
+}
+
+fn random_bytes() -> Result<[u8; 32], RecError> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|e| RecError::Encryption(format!("no random numbers available: {}", e)))?;
+    Ok(bytes)
+}
+
+/// Rewrites every `secret("..")` value in `source` as an `encrypted("..")`
+/// value sealed to `recipient`, returning the number of values changed.
+/// Everything else in the source is left as it was.
+pub fn encrypt_source(source: &str, recipient: &RecipientKey) -> Result<(String, usize), RecError> {
+    rewrite_calls(source, "secret", |plaintext| {
+        Ok(RecValue::Encrypted(encrypt(plaintext, recipient)?))
+    })
+}
+
+/// Rewrites every `encrypted("..")` value in `source` back into a
+/// `secret("..")` value, returning the number of values changed.
+pub fn decrypt_source(source: &str, key: &DecryptionKey) -> Result<(String, usize), RecError> {
+    rewrite_calls(source, "encrypted", |payload| {
+        Ok(RecValue::Secret(key.decrypt(payload)?))
+    })
+}
+
+fn rewrite_calls(
+    source: &str,
+    name: &str,
+    mut f: impl FnMut(&str) -> Result<RecValue, RecError>,
+) -> Result<(String, usize), RecError> {
+    let mut tree = syntax::parse(source)?;
+    let mut count = 0;
+    rewrite_node(&mut tree, name, &mut f, &mut count)?;
+    Ok((tree.to_string(), count))
+}
+
+fn rewrite_node(
+    node: &mut Node,
+    name: &str,
+    f: &mut dyn FnMut(&str) -> Result<RecValue, RecError>,
+    count: &mut usize,
+) -> Result<(), RecError> {
+    for child in &mut node.children {
+        let Element::Node(child) = child else {
+            continue;
+        };
+        if let Some(arg) = call_argument(child, name) {
+            *child = value(f(&arg)?);
+            *count += 1;
+        } else {
+            rewrite_node(child, name, f, count)?;
+        }
+    }
+    Ok(())
+}
+
+/// The string argument of a value node written `name("..")`.
+fn call_argument(node: &Node, name: &str) -> Option<String> {
+    if node.kind != NodeKind::Value {
+        return None;
+    }
+    let ident = node.first_token()?;
+    if ident.kind != TokenKind::Ident || ident.text != name {
+        return None;
+    }
+    let args = node.node(NodeKind::Tuple)?;
+    let mut values = args.nodes();
+    let (Some(arg), None) = (values.next(), values.next()) else {
+        return None;
+    };
+    match arg.first_token()? {
+        token if token.kind == TokenKind::String => crate::parser::unquote(&token.text),
+        _ => None,
+    }
+}
@@ -0,0 +1,194 @@
+//! Environment variables in documents: `env("PORT", 8080)` values and
+//! `"${HOST}"` interpolation in strings.
+
+use crate::{ByteEncoding, EnumVariantData, RecType, RecValue};
+use std::collections::HashMap;
+
+/// Where a [`Loader`](crate::Loader) reads environment variables from.
+/// Tests can pass a `HashMap<String, String>` instead of the process
+/// environment.
+pub trait EnvSource: Send + Sync {
+    /// The variable's value, or `None` if it is not set.
+    fn var(&self, name: &str) -> Option<String>;
+}
+
+/// The environment of the running process.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessEnv;
+
+impl EnvSource for ProcessEnv {
+    fn var(&self, name: &str) -> Option<String> {
+        std::env::var(name).ok()
+    }
+}
+
+impl EnvSource for HashMap<String, String> {
+    fn var(&self, name: &str) -> Option<String> {
+        self.get(name).cloned()
+    }
+}
+
+/// A piece of a string literal: text, or a `${NAME}` reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Part<'a> {
+    Text(String),
+    Var(&'a str),
+}
+
+/// Splits a string literal into text and `${NAME}` references. `$${` is a
+/// literal `${`, and a `${` that is not followed by a name and `}` is left
+/// as text.
+pub(crate) fn split_template(text: &str) -> Vec<Part<'_>> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut rest = text;
+    while let Some(i) = rest.find('$') {
+        literal.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if let Some(after) = rest.strip_prefix("$${") {
+            literal.push_str("${");
+            rest = after;
+        } else if let Some(name) = rest.strip_prefix("${").and_then(variable_name) {
+            if !literal.is_empty() {
+                parts.push(Part::Text(std::mem::take(&mut literal)));
+            }
+            parts.push(Part::Var(name));
+            rest = &rest[name.len() + 3..];
+        } else {
+            literal.push('$');
+            rest = &rest[1..];
+        }
+    }
+    literal.push_str(rest);
+    if !literal.is_empty() || parts.is_empty() {
+        parts.push(Part::Text(literal));
+    }
+    parts
+}
+
+/// The name at the start of `s` if it is followed by `}`.
+fn variable_name(s: &str) -> Option<&str> {
+    let end = s.find('}')?;
+    let name = &s[..end];
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then_some(name)
+}
+
+/// Escapes `${` in a plain string so it prints back as text rather than a
+/// reference.
+pub(crate) fn escape_template(text: &str) -> String {
+    text.replace("${", "$${")
+}
+
+/// Fills in the `${NAME}` references of a string literal. The error names
+/// the first variable that is not set.
+pub(crate) fn interpolate(template: &str, env: &dyn EnvSource) -> Result<String, String> {
+    let mut out = String::new();
+    for part in split_template(template) {
+        match part {
+            Part::Text(text) => out.push_str(&text),
+            Part::Var(name) => out.push_str(&env.var(name).ok_or_else(|| name.to_string())?),
+        }
+    }
+    Ok(out)
+}
+
+/// Reads the text of an environment variable as a value of type `ty`, or
+/// a string if the position is untyped. `is_enum` says whether `ty` names
+/// an enum. `None` if the text cannot be read as that type at all; ranges
+/// and formats are left to the validator.
+pub(crate) fn coerce(text: &str, ty: Option<(&RecType, bool)>) -> Option<RecValue> {
+    let text = text.to_string();
+    let Some((ty, is_enum)) = ty else {
+        return Some(RecValue::String(text));
+    };
+    Some(match ty {
+        RecType::String | RecType::Any => RecValue::String(text),
+        RecType::Int | RecType::SizedInt(_) => match text.trim().parse::<i64>() {
+            Ok(i) => RecValue::Int(i),
+            Err(_) => RecValue::UInt(text.trim().parse().ok()?),
+        },
+        RecType::Float => RecValue::Float(text.trim().parse().ok()?),
+        RecType::Decimal(_) => RecValue::Decimal(text.trim().parse().ok()?),
+        RecType::Bool => match text.trim().to_ascii_lowercase().as_str() {
+            "true" => RecValue::Bool(true),
+            "false" => RecValue::Bool(false),
+            _ => return None,
+        },
+        RecType::Url(_) => RecValue::Url(text),
+        RecType::Socket | RecType::Socket4 | RecType::Socket6 => RecValue::Socket(text),
+        RecType::HostPort => RecValue::HostPort(text),
+        RecType::Ip => RecValue::Ip(text),
+        RecType::Cidr => RecValue::Cidr(text),
+        RecType::Mac => RecValue::Mac(text),
+        RecType::Date(_) => RecValue::Date(text),
+        RecType::Time(_) => RecValue::Time(text),
+        RecType::DateTime(_) => RecValue::DateTime(text),
+        RecType::Pubkey(_) => RecValue::Pubkey(text),
+        RecType::Secp256k1 => RecValue::Secp256k1(text),
+        RecType::EvmAddress => RecValue::EvmAddress(text),
+        RecType::Secret => RecValue::Secret(text.into()),
+        RecType::Bytes(Some(ByteEncoding::Hex), _) => RecValue::Hex(text),
+        RecType::Bytes(Some(ByteEncoding::Base64), _) => RecValue::Base64(text),
+        RecType::Object(name) | RecType::Enum(name) if is_enum => RecValue::EnumVariant {
+            enum_name: name.clone(),
+            variant: text.trim().to_string(),
+            data: EnumVariantData::Unit,
+        },
+        // Durations, sizes, arrays, tuples and objects are written as REC,
+        // as plain literals only: a variable cannot name another variable,
+        // a file or a secret.
+        _ => {
+            let value = crate::parser::parse_value(&text).ok()?;
+            if !is_literal(&value) {
+                return None;
+            }
+            value
+        }
+    })
+}
+
+/// Whether `value` holds nothing that a [`Loader`](crate::Loader) would
+/// evaluate.
+fn is_literal(value: &RecValue) -> bool {
+    match value {
+        RecValue::Env { .. }
+        | RecValue::Template(_)
+        | RecValue::KeypairPubkey(_)
+        | RecValue::Pda(_)
+        | RecValue::SecretRef { .. }
+        | RecValue::Encrypted(_) => false,
+        RecValue::Array(values) | RecValue::Tuple(values) => values.iter().all(is_literal),
+        RecValue::Object(obj) => obj.fields.values().all(is_literal),
+        RecValue::EnumVariant { data, .. } => match data {
+            EnumVariantData::Unit => true,
+            EnumVariantData::Tuple(values) => values.iter().all(is_literal),
+            EnumVariantData::Struct(fields) => fields.values().all(is_literal),
+        },
+        _ => true,
+    }
+}
+
+/// The type an untyped `env(..)` takes from its default, so that
+/// `env("PORT", 8080)` still reads as an integer.
+pub(crate) fn type_of_default(default: &RecValue) -> Option<RecType> {
+    Some(match default {
+        RecValue::Int(_) | RecValue::UInt(_) => RecType::Int,
+        RecValue::Float(_) => RecType::Float,
+        RecValue::Decimal(_) => RecType::Decimal(Default::default()),
+        RecValue::Bool(_) => RecType::Bool,
+        RecValue::Url(_) => RecType::Url(Default::default()),
+        RecValue::Socket(_) => RecType::Socket,
+        RecValue::HostPort(_) => RecType::HostPort,
+        RecValue::Ip(_) => RecType::Ip,
+        RecValue::Cidr(_) => RecType::Cidr,
+        RecValue::Duration(_) => RecType::Duration,
+        RecValue::Size(_) => RecType::Size,
+        RecValue::Secret(_) => RecType::Secret,
+        _ => return None,
+    })
+}
@@ -23,15 +23,94 @@ pub enum RecError {
     #[error("Invalid socket address: {0}")]
     InvalidSocket(String),
 
+    #[error("Invalid IP address: {0}")]
+    InvalidIp(String),
+
+    #[error("Invalid CIDR block: {0}")]
+    InvalidCidr(String),
+
+    #[error("Invalid MAC address: {0}")]
+    InvalidMac(String),
+
+    #[error("Invalid byte string: {0}")]
+    InvalidBytes(String),
+
+    #[error("Invalid decimal: {0}")]
+    InvalidDecimal(String),
+
+    #[error("Invalid date or time: {0}")]
+    InvalidDateTime(String),
+
     #[error("Invalid pubkey: {0}")]
     InvalidPubkey(String),
 
+    #[error("Invalid keypair file: {0}")]
+    InvalidKeypair(String),
+
+    #[error("Invalid secp256k1 public key: {0}")]
+    InvalidSecp256k1Key(String),
+
+    #[error("Invalid EVM address: {0}")]
+    InvalidEvmAddress(String),
+
+    #[error("Missing secret: {0}")]
+    MissingSecret(String),
+
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+
+    #[error("Environment variable error: {0}")]
+    EnvVar(String),
+
     #[error("Include file not found: {0}")]
     IncludeNotFound(String),
 
     #[error("Duplicate key: {0}")]
     DuplicateKey(String),
 
+    #[error("Conflicting types for field '{field}' in {type_name}: {existing} vs {incoming}")]
+    ConflictingFieldType {
+        type_name: String,
+        field: String,
+        existing: String,
+        incoming: String,
+    },
+
     #[error("Missing required field: {0}")]
     MissingField(String),
+
+    #[error("Serialize error: {0}")]
+    Serialize(String),
+
+    #[error("Deserialize error at {path}: {message}")]
+    Deserialize { path: String, message: String },
+}
+
+impl RecError {
+    /// Records where a deserialize error happened, unless a more specific
+    /// location was already recorded further down.
+    pub(crate) fn at(self, path: &str) -> Self {
+        match self {
+            RecError::Deserialize { path: p, message } if p.is_empty() => RecError::Deserialize {
+                path: if path.is_empty() { "<root>" } else { path }.to_string(),
+                message,
+            },
+            other => other,
+        }
+    }
+}
+
+impl serde::ser::Error for RecError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        RecError::Serialize(msg.to_string())
+    }
+}
+
+impl serde::de::Error for RecError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        RecError::Deserialize {
+            path: String::new(),
+            message: msg.to_string(),
+        }
+    }
 }
@@ -1,13 +1,36 @@
 pub mod ast;
+pub mod de;
+pub mod decimal;
+pub mod edit;
+pub mod encryption;
+pub mod env;
 pub mod error;
+pub mod loader;
+pub mod net;
 pub mod parser;
+pub mod printer;
+pub mod pubkey;
+pub mod secret;
+pub mod ser;
+pub mod syntax;
+pub mod units;
 pub mod validator;
 pub mod value;
 
 pub use ast::*;
+pub use de::{from_str, from_value, from_value_with};
+pub use decimal::Decimal;
+pub use encryption::{DecryptionKey, RecipientKey};
+pub use env::{EnvSource, ProcessEnv};
 pub use error::RecError;
+pub use loader::Loader;
+pub use net::{Cidr, MacAddr};
 pub use parser::parse_rec;
+pub use pubkey::{EvmAddress, Pda, PdaSeed, Pubkey, Secp256k1Pubkey};
+pub use secret::{DirProvider, EnvProvider, MemoryProvider, Secret, SecretProvider};
+pub use ser::{to_string_pretty, to_value};
 pub use validator::validate;
+pub use value::RecDeserialize;
 
 #[cfg(test)]
 mod tests {
@@ -72,6 +95,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_separators_and_comments() {
+        let input = r#"
+        // Line comments and /* block comments */ are whitespace.
+        @enum Mode { FAST, SLOW, }
+        @type Limits { min: int, max: int
+            tags: [stringy] }
+        {
+            a: 1, b: 2
+            c: [1, 2
+                3,]  /* trailing comma */
+            mode: Mode.FAST,
+        }"#;
+
+        let doc = parse_rec(input).unwrap();
+        validate(&doc).unwrap();
+        assert_eq!(
+            doc.root.fields.keys().collect::<Vec<_>>(),
+            vec!["a", "b", "c", "mode"]
+        );
+        assert_eq!(doc.enum_definitions["Mode"].variants.len(), 2);
+        let limits = &doc.type_definitions["Limits"].fields;
+        assert_eq!(limits.len(), 3);
+        // `stringy` names a type; it is not the `string` keyword.
+        assert_eq!(
+            limits["tags"].ty,
+            RecType::Array(Box::new(RecType::Object("stringy".into())))
+        );
+
+        assert!(matches!(
+            parse_rec("{ a: 1 } b"),
+            Err(RecError::ParseError(e)) if e.contains("trailing input")
+        ));
+    }
+
     #[test]
     fn test_serde() {
         use serde::{Deserialize, Serialize};
@@ -107,9 +165,2136 @@ mod tests {
             Database::Postgres { host, port, ssl } => {
                 assert_eq!(host, "localhost");
                 assert_eq!(port, 5432);
-                assert_eq!(ssl, true);
+                assert!(ssl);
             }
             _ => panic!("Expected Postgres variant"),
         }
     }
+
+    #[test]
+    fn test_type_composition() {
+        let input = r#"
+        @type DbBase {
+            host: string
+            port: int
+            password?: string
+        }
+
+        @type Pooled {
+            max_connections: int
+        }
+
+        @type PostgresConfig extends DbBase {
+            ...Pooled
+            password: string
+            ssl: bool
+        }
+
+        {
+            db: PostgresConfig {
+                host: "localhost"
+                port: 5432
+                password: "hunter2"
+                max_connections: 20
+                ssl: true
+            }
+        }"#;
+
+        let doc = parse_rec(input).unwrap();
+        validate(&doc).unwrap();
+
+        let pg = &doc.type_definitions["PostgresConfig"];
+        assert_eq!(pg.bases, vec!["DbBase", "Pooled"]);
+        assert_eq!(
+            pg.fields.keys().collect::<Vec<_>>(),
+            vec!["host", "port", "password", "max_connections", "ssl"]
+        );
+        assert!(!pg.fields["password"].optional);
+
+        let missing = input.replace("max_connections: 20", "");
+        let doc = parse_rec(&missing).unwrap();
+        assert!(matches!(
+            validate(&doc),
+            Err(RecError::MissingField(f)) if f == "PostgresConfig.max_connections"
+        ));
+
+        // A typed object of a composed type stands in for any of its bases.
+        let input = r#"
+        @type DbBase {
+            host: string
+            password?: string
+        }
+        @type Pooled { max_connections: int }
+        @type PostgresConfig extends DbBase {
+            ...Pooled
+            password: string
+        }
+        @type Cluster {
+            primary: DbBase
+            replicas: [Pooled]
+        }
+        {
+            cluster: Cluster {
+                primary: PostgresConfig { host: "a", password: "x", max_connections: 5 }
+                replicas: [PostgresConfig { host: "b", password: "y", max_connections: 1 }]
+            }
+        }"#;
+        let doc = parse_rec(input).unwrap();
+        validate(&doc).unwrap();
+        let printed = printer::Printer::default().print_document(&doc);
+        assert!(
+            printed
+                .contains("@type PostgresConfig extends DbBase, Pooled {\n    password: string\n}")
+        );
+        assert_eq!(
+            parse_rec(&printed).unwrap().type_definitions,
+            doc.type_definitions
+        );
+        let doc = parse_rec(&input.replace("primary: PostgresConfig", "primary: Pooled")).unwrap();
+        assert_eq!(
+            validate(&doc).unwrap_err().to_string(),
+            "Validation error: Type mismatch at 'cluster.primary': expected DbBase, got object"
+        );
+    }
+
+    #[test]
+    fn test_type_composition_conflict() {
+        let input = r#"
+        @type DbBase {
+            port: int
+        }
+
+        @type MySqlConfig extends DbBase {
+            port: string
+        }
+
+        {}"#;
+
+        match parse_rec(input) {
+            Err(RecError::ConflictingFieldType {
+                type_name, field, ..
+            }) => {
+                assert_eq!(type_name, "MySqlConfig");
+                assert_eq!(field, "port");
+            }
+            other => panic!("Expected conflicting field type, got {:?}", other),
+        }
+
+        let cyclic = r#"
+        @type A extends B { a: int }
+        @type B { ...A }
+        {}"#;
+        assert!(parse_rec(cyclic).is_err());
+    }
+
+    #[test]
+    fn test_tuples_and_fixed_arrays() {
+        use serde::Deserialize;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Display {
+            window: (u32, u32),
+            rgb: [u8; 3],
+        }
+
+        let input = r#"
+        @type Display {
+            window: (int, int)
+            rgb: [int; 3]
+        }
+
+        {
+            display: Display {
+                window: (1920, 1080)
+                rgb: [255, 128, 0]
+            }
+        }"#;
+
+        let doc = parse_rec(input).unwrap();
+        validate(&doc).unwrap();
+        assert_eq!(
+            doc.type_definitions["Display"].fields["window"].ty,
+            RecType::Tuple(vec![RecType::Int, RecType::Int])
+        );
+
+        let display = doc.root.fields.get("display").unwrap();
+        let window = display.get("window").unwrap();
+        assert_eq!(<(i64, i64)>::from_rec(window).unwrap(), (1920, 1080));
+        let rgb = display.get("rgb").unwrap();
+        assert_eq!(<[i64; 3]>::from_rec(rgb).unwrap(), [255, 128, 0]);
+
+        let json = serde_json::to_value(display).unwrap();
+        let display: Display = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            display,
+            Display {
+                window: (1920, 1080),
+                rgb: [255, 128, 0],
+            }
+        );
+
+        let short = parse_rec(&input.replace("[255, 128, 0]", "[255, 128]")).unwrap();
+        assert!(validate(&short).is_err());
+        let wrong = parse_rec(&input.replace("(1920, 1080)", "(1920, \"tall\")")).unwrap();
+        assert!(validate(&wrong).is_err());
+    }
+
+    #[test]
+    fn test_unit_enum_values() {
+        use crate::value::{SerializeOptions, UnitVariantFormat};
+
+        let input = r#"
+        @enum LogLevel {
+            DEBUG = 10
+            INFO = 20
+            TRACE
+        }
+
+        @enum Region {
+            UsEast = "us-east-1"
+            EuWest = "eu-west-1"
+        }
+
+        {
+            level: LogLevel.INFO
+            trace: LogLevel.TRACE
+            regions: [Region.UsEast, Region.EuWest]
+        }"#;
+
+        let doc = parse_rec(input).unwrap();
+        validate(&doc).unwrap();
+        assert_eq!(
+            doc.enum_definitions["LogLevel"].variants[1],
+            EnumVariant::Unit("INFO".to_string(), Some(EnumDiscriminant::Int(20)))
+        );
+
+        let by_name = serde_json::to_value(&doc.root).unwrap();
+        assert_eq!(by_name["level"], "LogLevel.INFO");
+
+        let options = SerializeOptions {
+            unit_variants: UnitVariantFormat::Value,
+            ..Default::default()
+        };
+        let by_value = serde_json::to_value(doc.serialize_with(&options)).unwrap();
+        assert_eq!(by_value["level"], 20);
+        assert_eq!(by_value["trace"], "LogLevel.TRACE");
+        assert_eq!(by_value["regions"][0], "us-east-1");
+
+        let duplicate = parse_rec(&input.replace("INFO = 20", "INFO = 10")).unwrap();
+        assert!(validate(&duplicate).is_err());
+    }
+
+    #[test]
+    fn test_enum_shorthand() {
+        let input = r#"
+        @enum Environment {
+            DEVELOPMENT
+            PRODUCTION
+        }
+
+        @enum Cache {
+            NoCache
+            FixedTTL(int)
+        }
+
+        @type ServerConfig {
+            environment: Environment
+            fallbacks: [Environment]
+            cache: Cache
+        }
+
+        {
+            server: ServerConfig {
+                environment: .DEVELOPMENT
+                fallbacks: [PRODUCTION, .DEVELOPMENT]
+                cache: .FixedTTL(300)
+            }
+        }"#;
+
+        let doc = parse_rec(input).unwrap();
+        validate(&doc).unwrap();
+
+        let server = doc.root.fields.get("server").unwrap();
+        assert_eq!(
+            server.get("environment").unwrap(),
+            &RecValue::EnumVariant {
+                enum_name: "Environment".to_string(),
+                variant: "DEVELOPMENT".to_string(),
+                data: EnumVariantData::Unit,
+            }
+        );
+        match server.get("fallbacks").unwrap() {
+            RecValue::Array(values) => assert!(values.iter().all(
+                |v| matches!(v, RecValue::EnumVariant { enum_name, .. } if enum_name == "Environment")
+            )),
+            other => panic!("Expected array, got {:?}", other),
+        }
+
+        let unknown = parse_rec(&input.replace(".DEVELOPMENT\n", ".STAGING\n")).unwrap();
+        assert!(matches!(
+            validate(&unknown),
+            Err(RecError::UnknownEnumVariant { .. })
+        ));
+
+        let untyped = parse_rec("{ environment: .DEVELOPMENT }").unwrap();
+        assert!(validate(&untyped).is_err());
+
+        // A misspelled keyword reads as a shorthand and is named as such.
+        let typo =
+            parse_rec("@type Cache { enabled: bool }\n{ cache: Cache { enabled: ture } }").unwrap();
+        assert_eq!(
+            validate(&typo).unwrap_err().to_string(),
+            "Validation error: Unknown identifier 'ture' at 'cache.enabled': expected bool"
+        );
+        let typo = parse_rec("{ enabled: ture }").unwrap();
+        assert!(
+            validate(&typo)
+                .unwrap_err()
+                .to_string()
+                .contains("Unknown identifier 'ture' at 'enabled'")
+        );
+    }
+
+    #[test]
+    fn test_enum_representations() {
+        use crate::value::{EnumRepr, SerializeOptions};
+        use serde::Deserialize;
+        use serde_json::json;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum CacheStrategy {
+            NoCache,
+            FixedTTL(u32),
+            SlidingWindow(u32, u32),
+            Custom { strategy: String },
+        }
+
+        let input = r#"
+        @enum Cache {
+            NoCache
+            FixedTTL(int)
+            SlidingWindow(int, int)
+            Custom { strategy: string }
+        }
+
+        {
+            caches: [
+                Cache.NoCache,
+                Cache.FixedTTL(300),
+                Cache.SlidingWindow(100, 600),
+                Cache.Custom { strategy: "lru" }
+            ]
+        }"#;
+        let doc = parse_rec(input).unwrap();
+        validate(&doc).unwrap();
+        let caches = doc.root.fields.get("caches").unwrap();
+
+        let to_json = |enum_repr, enum_prefix| {
+            let options = SerializeOptions {
+                enum_repr,
+                enum_prefix,
+                ..Default::default()
+            };
+            serde_json::to_value(caches.serialize_with(&options)).unwrap()
+        };
+
+        let tagged = serde_json::to_value(caches).unwrap();
+        assert_eq!(
+            tagged,
+            json!([
+                "Cache.NoCache",
+                { "variant": "FixedTTL", "data": 300 },
+                { "variant": "SlidingWindow", "data": [100, 600] },
+                { "variant": "Custom", "data": { "strategy": "lru" } }
+            ])
+        );
+
+        let external = to_json(EnumRepr::External, false);
+        assert_eq!(
+            external,
+            json!([
+                "NoCache",
+                { "FixedTTL": 300 },
+                { "SlidingWindow": [100, 600] },
+                { "Custom": { "strategy": "lru" } }
+            ])
+        );
+        let parsed: Vec<CacheStrategy> = serde_json::from_value(external).unwrap();
+        assert_eq!(parsed[1], CacheStrategy::FixedTTL(300));
+
+        let internal = SerializeOptions {
+            enum_repr: EnumRepr::Internal { tag: "type".into() },
+            ..Default::default()
+        };
+        assert!(serde_json::to_value(caches.serialize_with(&internal)).is_err());
+        let custom = match caches {
+            RecValue::Array(values) => &values[3],
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            serde_json::to_value(custom.serialize_with(&internal)).unwrap(),
+            json!({ "type": "Custom", "strategy": "lru" })
+        );
+
+        let adjacent = to_json(
+            EnumRepr::Adjacent {
+                tag: "t".into(),
+                content: "c".into(),
+            },
+            true,
+        );
+        assert_eq!(adjacent[0], json!({ "t": "Cache.NoCache" }));
+        assert_eq!(adjacent[1], json!({ "t": "Cache.FixedTTL", "c": 300 }));
+
+        let untagged = to_json(EnumRepr::Untagged, false);
+        assert_eq!(
+            untagged,
+            json!([null, 300, [100, 600], { "strategy": "lru" }])
+        );
+    }
+
+    #[test]
+    fn test_native_deserialize() {
+        use serde::Deserialize;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Environment {
+            Development,
+            Production,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Database {
+            Postgres { host: String, port: u16 },
+            Sqlite(String),
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Server<'a> {
+            host: &'a str,
+            port: u16,
+            environment: Environment,
+            databases: Vec<Database>,
+            admin: Option<String>,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Config<'a> {
+            #[serde(borrow)]
+            server: Server<'a>,
+        }
+
+        let input = r#"
+        @enum Environment { Development, Production }
+        @enum Database {
+            Postgres { host: string, port: int }
+            Sqlite(string)
+        }
+
+        {
+            server: {
+                host: "0.0.0.0"
+                port: 8080
+                environment: Environment.Production
+                databases: [
+                    Database.Postgres { host: "localhost", port: 5432 },
+                    Database.Sqlite("/tmp/app.db")
+                ]
+                admin: null
+            }
+        }"#;
+
+        let doc = parse_rec(input).unwrap();
+        let root = RecValue::Object(doc.root);
+        let config: Config = from_value(&root).unwrap();
+        assert_eq!(config.server.host, "0.0.0.0");
+        assert_eq!(config.server.environment, Environment::Production);
+        assert_eq!(
+            config.server.databases,
+            vec![
+                Database::Postgres {
+                    host: "localhost".to_string(),
+                    port: 5432
+                },
+                Database::Sqlite("/tmp/app.db".to_string()),
+            ]
+        );
+        assert_eq!(config.server.admin, None);
+
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Owned {
+            server: OwnedServer,
+        }
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct OwnedServer {
+            port: u16,
+            databases: Vec<Database>,
+            admin: Option<String>,
+        }
+
+        let err = from_str::<Owned>(&input.replace("8080", "70000")).unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                RecError::Deserialize { path, .. } if path == "server.port"
+            ),
+            "{}",
+            err
+        );
+
+        let err = from_str::<Owned>(&input.replace("admin: null", "admin: [1]")).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("Deserialize error at server.admin:"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        use serde::{Deserialize, Serialize};
+        use std::collections::BTreeMap;
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        enum Mode {
+            Off,
+            Fixed(i64),
+            Range(i64, i64),
+            Custom { name: String, weight: f64 },
+        }
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Settings {
+            title: String,
+            ratio: f64,
+            point: (i64, i64),
+            modes: Vec<Mode>,
+            headers: BTreeMap<String, String>,
+            limit: Option<i64>,
+        }
+
+        let settings = Settings {
+            title: "say \"hi\"\n\tand leave \\".to_string(),
+            ratio: 2.0,
+            point: (3, -4),
+            modes: vec![
+                Mode::Off,
+                Mode::Fixed(7),
+                Mode::Range(1, 9),
+                Mode::Custom {
+                    name: "slow".to_string(),
+                    weight: 0.5,
+                },
+            ],
+            headers: BTreeMap::from([
+                ("Content-Type".to_string(), "text/plain".to_string()),
+                ("x".to_string(), "y".to_string()),
+            ]),
+            limit: None,
+        };
+
+        let value = to_value(&settings).unwrap();
+        let text = to_string_pretty(&settings).unwrap();
+        assert!(text.contains("Mode.Range(1, 9)"));
+        assert!(text.contains("\"Content-Type\": \"text/plain\""));
+
+        let doc = parse_rec(&text).unwrap();
+        let root = RecValue::Object(doc.root);
+        assert_eq!(root, value);
+        let back: Settings = from_value(&root).unwrap();
+        assert_eq!(back, settings);
+
+        assert!(to_value(&f64::NAN).is_err());
+        assert!(to_string_pretty(&[1, 2]).is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        use crate::printer::{Commas, KeyOrder, Printer};
+
+        let source = r#"// Service settings
+@enum Mode { Off, Fixed(int,int), Level = 3 }
+@type Base { id: int }
+@type Service extends Base{name:string, port : int // listening port
+  ...Base
+  ratios: [float;2]
+}
+{ service: Service { id: 1, name: "api", port: 8080, ratios: [0.5,0.25] }
+  /* modes in order */ modes: [Mode.Off,Mode.Fixed(1,2)]
+  "x-header": "v"  // quoted key
+  mode: .Off
+}
+"#;
+        let tree = syntax::parse(source).unwrap();
+        assert_eq!(tree.to_string(), source);
+
+        let formatted = Printer::new().format(&tree);
+        assert_eq!(
+            formatted,
+            r#"// Service settings
+@enum Mode {
+    Off,
+    Fixed(int, int),
+    Level = 3
+}
+
+@type Base {
+    id: int
+}
+
+@type Service extends Base {
+    name: string
+    port: int  // listening port
+    ...Base
+    ratios: [float; 2]
+}
+
+{
+    service: Service {
+        id: 1
+        name: "api"
+        port: 8080
+        ratios: [0.5, 0.25]
+    }
+    /* modes in order */
+    modes: [Mode.Off, Mode.Fixed(1, 2)]
+    "x-header": "v"  // quoted key
+    mode: .Off
+}
+"#
+        );
+        assert_eq!(Printer::new().format_str(&formatted).unwrap(), formatted);
+        assert_eq!(parse_rec(&formatted).unwrap(), parse_rec(source).unwrap());
+
+        let printer = Printer {
+            indent: "  ".to_string(),
+            commas: Commas::Trailing,
+            key_order: KeyOrder::Sorted,
+            ..Printer::new()
+        };
+        let sorted = printer.format_str("{ b: 1, a: { d: 2, c: 3 } }").unwrap();
+        assert_eq!(
+            sorted,
+            "{\n  a: {\n    c: 3,\n    d: 2,\n  },\n  b: 1,\n}\n"
+        );
+
+        // Printing a parsed document keeps its definitions but not comments.
+        let doc = parse_rec(source).unwrap();
+        let printed = Printer::new().print_document(&doc);
+        assert!(!printed.contains("//"));
+        assert_eq!(parse_rec(&printed).unwrap(), doc);
+
+        assert!(syntax::parse("{ a: [1, 2 }").is_err());
+    }
+
+    #[test]
+    fn test_edit_preserves_layout() {
+        use crate::edit::{DocumentMut, value};
+
+        let source = r#"#include "base.rec"
+
+// Deployed by CI
+{
+    server: {
+        host: "0.0.0.0"   // all interfaces
+        port: 8080
+
+        // Old setting, to be removed
+        legacy: true
+        origins: [
+            url("https://a.example.com"),
+            url("https://b.example.com")
+        ]
+    }
+    tags: ["x", "y"]
+    limits: { rps: 10, burst: 20 }
+}
+"#;
+        let mut doc: DocumentMut = source.parse().unwrap();
+        assert_eq!(doc.to_string(), source);
+        assert_eq!(
+            doc["server"]["port"].to_value().unwrap(),
+            RecValue::Int(8080)
+        );
+
+        doc["server"]["port"] = value(8081);
+        doc["server"]["region"] = value("eu-west-1");
+        assert!(doc["server"].remove("legacy").is_some());
+        doc["server"]["origins"]
+            .push(value(RecValue::Url("https://c.example.com".to_string())))
+            .unwrap();
+        doc["tags"].push(value("z")).unwrap();
+        doc["limits"].remove("rps");
+        doc["tags"][0] = value("w");
+
+        assert_eq!(
+            doc.to_string(),
+            r#"#include "base.rec"
+
+// Deployed by CI
+{
+    server: {
+        host: "0.0.0.0"   // all interfaces
+        port: 8081
+
+        origins: [
+            url("https://a.example.com"),
+            url("https://b.example.com"),
+            url("https://c.example.com")
+        ]
+        region: "eu-west-1"
+    }
+    tags: ["w", "y", "z"]
+    limits: { burst: 20 }
+}
+"#
+        );
+        assert!(parse_rec(&doc.to_string()).is_ok());
+        assert!(doc["tags"].insert("k", value(1)).is_err());
+    }
+
+    #[test]
+    fn test_typed_domain_values() {
+        use serde::Deserialize;
+        use std::net::SocketAddr;
+
+        #[derive(Debug, Deserialize)]
+        struct Node {
+            endpoint: url::Url,
+            bind: SocketAddr,
+            authority: Pubkey,
+        }
+
+        let source = r#"{
+            endpoint: url("https://api.example.com/v1")
+            bind: socket("127.0.0.1:8899")
+            authority: pubkey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA")
+        }"#;
+        let node: Node = from_str(source).unwrap();
+        assert_eq!(node.endpoint.host_str(), Some("api.example.com"));
+        assert_eq!(node.bind.port(), 8899);
+        assert_eq!(
+            node.authority.to_string(),
+            "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+        );
+        assert_eq!(node.authority.as_bytes()[0], 6);
+
+        let doc = parse_rec(source).unwrap();
+        let root = RecValue::Object(doc.root);
+        let bind = root.get("bind").unwrap();
+        assert_eq!(bind.to_socket_addr().unwrap(), node.bind);
+        assert_eq!(SocketAddr::from_rec(bind).unwrap(), node.bind);
+        assert_eq!(
+            Pubkey::from_rec(root.get("authority").unwrap()).unwrap(),
+            node.authority
+        );
+        assert!(matches!(bind.to_url(), Err(RecError::TypeError { .. })));
+
+        assert!(matches!(
+            "abc".parse::<Pubkey>(),
+            Err(RecError::InvalidPubkey(_))
+        ));
+        assert_eq!(
+            Pubkey::new([0; 32]).to_string(),
+            "11111111111111111111111111111111"
+        );
+    }
+
+    #[test]
+    fn test_socket_families() {
+        let source = r#"
+@type Net {
+    any: socket
+    v4: socket4
+    v6: socket6
+    cache: hostport
+    peers: [hostport]
+}
+
+{
+    net: Net {
+        any: socket("[::1]:8080")
+        v4: socket("10.0.0.1:53")
+        v6: socket("[fe80::1]:443")
+        cache: hostport("redis.internal:6379")
+        peers: [hostport("[::1]:9000"), socket("127.0.0.1:9001")]
+    }
+}
+"#;
+        let doc = parse_rec(source).unwrap();
+        validate(&doc).unwrap();
+        let root = RecValue::Object(doc.root);
+        let net = root.get("net").unwrap();
+        assert_eq!(
+            net.get("cache").unwrap().to_host_port().unwrap(),
+            ("redis.internal".to_string(), 6379)
+        );
+        assert!(net.get("any").unwrap().to_socket_addr().unwrap().is_ipv6());
+
+        let invalid = |from: &str, to: &str| {
+            let doc = parse_rec(&source.replacen(from, to, 1)).unwrap();
+            match validate(&doc) {
+                Err(RecError::InvalidSocket(message)) => message,
+                other => panic!("expected a socket error, got {:?}", other),
+            }
+        };
+        assert!(invalid("10.0.0.1:53", "[::1]:53").contains("expected an IPv4 address"));
+        assert!(invalid("[fe80::1]:443", "1.2.3.4:443").contains("expected an IPv6 address"));
+        assert!(invalid("[::1]:8080", "::1:8080").contains("must be in brackets"));
+        assert!(invalid("[::1]:8080", "db.local:8080").contains("use a hostport field"));
+        assert!(invalid("redis.internal:6379", "redis.internal:99999").contains("net.cache"));
+        assert!(invalid("redis.internal:6379", "-bad-:1").contains("not a valid host name"));
+    }
+
+    #[test]
+    fn test_network_addresses() {
+        use serde::Deserialize;
+        use std::net::IpAddr;
+
+        #[derive(Debug, Deserialize)]
+        struct Firewall {
+            gateway: IpAddr,
+            allow: Vec<Cidr>,
+            device: MacAddr,
+        }
+
+        let source = r#"
+@type Firewall {
+    gateway: ip
+    allow: [cidr]
+    device: mac
+}
+
+{
+    firewall: Firewall {
+        gateway: ip("10.0.0.1")
+        allow: [cidr("10.0.0.0/8"), cidr("fd00::/8")]
+        device: mac("00:1A:2b:3c:4d:5e")
+    }
+}
+"#;
+        let doc = parse_rec(source).unwrap();
+        validate(&doc).unwrap();
+        let root = RecValue::Object(doc.root);
+        let firewall: Firewall = from_value(root.get("firewall").unwrap()).unwrap();
+        assert_eq!(firewall.gateway, "10.0.0.1".parse::<IpAddr>().unwrap());
+        assert!(firewall.allow[0].contains(&firewall.gateway));
+        assert!(!firewall.allow[1].contains(&firewall.gateway));
+        assert!(firewall.allow[1].contains(&"fd12::1".parse().unwrap()));
+        assert_eq!(firewall.device.to_string(), "00:1a:2b:3c:4d:5e");
+        assert_eq!(
+            "192.168.1.0/24".parse::<Cidr>().unwrap().network(),
+            "192.168.1.0".parse::<IpAddr>().unwrap()
+        );
+
+        let invalid = |from: &str, to: &str| {
+            let doc = parse_rec(&source.replacen(from, to, 1)).unwrap();
+            validate(&doc).unwrap_err()
+        };
+        assert!(matches!(
+            invalid("10.0.0.1", "10.0.0.256"),
+            RecError::InvalidIp(m) if m.contains("firewall.gateway")
+        ));
+        assert!(matches!(
+            invalid("/8\"", "/33\""),
+            RecError::InvalidCidr(m) if m.contains("longer than 32 bits")
+        ));
+        assert_eq!(
+            invalid("10.0.0.0/8", "10.0.0.1/8").to_string(),
+            "Invalid CIDR block: 10.0.0.1/8: host bits are set; the network is 10.0.0.0/8 at 'firewall.allow[0]'"
+        );
+        assert!(matches!(invalid("4d:5e", "4d"), RecError::InvalidMac(_)));
+        assert!(matches!(
+            invalid("ip(\"10.0.0.1\")", "\"10.0.0.1\""),
+            RecError::ValidationError(m) if m.contains("expected ip, got string")
+        ));
+    }
+
+    #[test]
+    fn test_durations_and_sizes() {
+        use crate::units::{DurationUnit, SizeUnit};
+        use crate::value::SerializeOptions;
+        use serde::{Deserialize, Serialize};
+        use std::time::Duration;
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Cache {
+            ttl: Duration,
+            timeout: Duration,
+            max_size: u64,
+        }
+
+        let source = r#"
+@type Cache {
+    ttl: duration
+    timeout: duration
+    max_size: size
+}
+
+{
+    cache: Cache {
+        ttl: 1h30m
+        timeout: 250ms
+        max_size: 512MiB
+    }
+}
+"#;
+        let doc = parse_rec(source).unwrap();
+        validate(&doc).unwrap();
+        let root = RecValue::Object(doc.root.clone());
+        let cache: Cache = from_value(root.get("cache").unwrap()).unwrap();
+        assert_eq!(
+            cache,
+            Cache {
+                ttl: Duration::from_secs(5400),
+                timeout: Duration::from_millis(250),
+                max_size: 512 << 20,
+            }
+        );
+        assert_eq!(
+            root.get("cache")
+                .unwrap()
+                .get("ttl")
+                .unwrap()
+                .to_duration()
+                .unwrap(),
+            cache.ttl
+        );
+        let max_size = root.get("cache").unwrap().get("max_size").unwrap();
+        assert_eq!(u64::from_rec(max_size).unwrap(), 512 << 20);
+        assert_eq!(u128::from_rec(max_size).unwrap(), 512 << 20);
+        assert!(u32::from_rec(max_size).is_err());
+        let written = to_value(&cache).unwrap();
+        assert_eq!(
+            written.get("ttl"),
+            Some(&RecValue::Duration(Duration::from_secs(5400)))
+        );
+
+        let json = serde_json::to_value(doc.serialize_with(&SerializeOptions::default())).unwrap();
+        assert_eq!(
+            json["cache"],
+            serde_json::json!({ "ttl": "1h30m", "timeout": "250ms", "max_size": "512MiB" })
+        );
+        let options = SerializeOptions {
+            duration_unit: Some(DurationUnit::Seconds),
+            size_unit: Some(SizeUnit::KB),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(doc.serialize_with(&options)).unwrap();
+        assert_eq!(
+            json["cache"],
+            serde_json::json!({ "ttl": 5400, "timeout": 0.25, "max_size": 536870.912 })
+        );
+
+        let parse = |literal: &str| parse_rec(&format!("{{ v: {} }}", literal)).map(|d| d.root);
+        assert_eq!(
+            parse("10GB").unwrap().fields["v"],
+            RecValue::Size(10_000_000_000)
+        );
+        assert_eq!(
+            printer::Printer::default().print_value(&RecValue::Size(10_000_000_000)),
+            "10GB"
+        );
+        assert!(parse("30m1h").is_err());
+        assert!(parse("10XB").is_err());
+    }
+
+    #[test]
+    fn test_sizes_into_wide_ints() {
+        let doc = parse_rec("{ small: 1KiB, large: 8GiB }").unwrap();
+        let small = &doc.root.fields["small"];
+        assert_eq!(u64::from_rec(small).unwrap(), 1024);
+        assert_eq!(u128::from_rec(small).unwrap(), 1024);
+        // Narrower types cannot hold every size, so they take none.
+        assert!(u16::from_rec(small).is_err());
+        assert!(i32::from_rec(small).is_err());
+
+        let large = &doc.root.fields["large"];
+        assert_eq!(u64::from_rec(large).unwrap(), 8 << 30);
+        assert_eq!(u128::from_rec(large).unwrap(), 8 << 30);
+        assert!(u32::from_rec(large).is_err());
+    }
+
+    #[test]
+    fn test_dates_and_times() {
+        use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+        use serde::Deserialize;
+
+        #[derive(Debug, Deserialize)]
+        struct Window {
+            day: NaiveDate,
+            opens: NaiveTime,
+            starts: DateTime<Utc>,
+        }
+
+        let source = r#"
+@type Window {
+    day: date(after: "2025-12-31")
+    opens: time
+    starts: datetime(after: "2026-01-01T00:00:00Z", before: "2027-01-01T00:00:00Z")
+}
+
+{
+    window: Window {
+        day: date("2026-03-14")
+        opens: time("23:30:00")
+        starts: datetime("2026-03-14T01:30:00+02:00")
+    }
+}
+"#;
+        let doc = parse_rec(source).unwrap();
+        validate(&doc).unwrap();
+        assert_eq!(
+            doc.type_definitions["Window"].fields["starts"]
+                .ty
+                .to_string(),
+            r#"datetime(after: "2026-01-01T00:00:00Z", before: "2027-01-01T00:00:00Z")"#
+        );
+        let printed = printer::Printer::default().print_document(&doc);
+        assert_eq!(parse_rec(&printed).unwrap(), doc);
+
+        let root = RecValue::Object(doc.root);
+        let window: Window = from_value(root.get("window").unwrap()).unwrap();
+        assert_eq!(window.day, NaiveDate::from_ymd_opt(2026, 3, 14).unwrap());
+        assert_eq!(window.opens, NaiveTime::from_hms_opt(23, 30, 0).unwrap());
+        assert_eq!(window.starts.to_rfc3339(), "2026-03-13T23:30:00+00:00");
+
+        let invalid = |from: &str, to: &str| {
+            let doc = parse_rec(&source.replacen(from, to, 1)).unwrap();
+            validate(&doc).unwrap_err().to_string()
+        };
+        assert!(invalid("2026-03-14\"", "2025-06-01\"").contains("must be after 2025-12-31"));
+        assert!(
+            invalid("2026-03-14T01:30:00+02:00", "2026-01-01T01:30:00+02:00")
+                .contains("must be after 2026-01-01T00:00:00Z")
+        );
+        assert!(invalid("23:30:00", "24:30:00").contains("window.opens"));
+        assert!(invalid("+02:00\")", "\")").contains("not a datetime"));
+        assert!(parse_rec("@type T { d: date(since: \"2026-01-01\") }\n{}").is_err());
+    }
+
+    #[test]
+    fn test_sized_ints() {
+        use serde::Deserialize;
+
+        #[derive(Debug, Deserialize)]
+        struct Token {
+            port: u16,
+            offset: i8,
+            supply: u128,
+        }
+
+        let source = r#"
+@type Token {
+    port: u16
+    offset: i8
+    supply: u128
+    count?: int
+}
+
+{
+    token: Token {
+        port: 8080
+        offset: -128
+        supply: 340282366920938463463374607431768211455
+    }
+}
+"#;
+        let doc = parse_rec(source).unwrap();
+        validate(&doc).unwrap();
+        let root = RecValue::Object(doc.root.clone());
+        let token: Token = from_value(root.get("token").unwrap()).unwrap();
+        assert_eq!(
+            (token.port, token.offset, token.supply),
+            (8080, -128, u128::MAX)
+        );
+        assert_eq!(
+            u128::from_rec(root.get("token").unwrap().get("supply").unwrap()).unwrap(),
+            u128::MAX
+        );
+        let json = serde_json::to_string(&doc.serialize_with(&Default::default())).unwrap();
+        assert!(json.contains(r#""supply":340282366920938463463374607431768211455"#));
+        assert_eq!(
+            to_value(&u64::MAX).unwrap(),
+            RecValue::UInt(u64::MAX.into())
+        );
+
+        let invalid = |from: &str, to: &str| {
+            let doc = parse_rec(&source.replacen(from, to, 1)).unwrap();
+            validate(&doc).unwrap_err().to_string()
+        };
+        assert!(
+            invalid("8080", "70000")
+                .contains("Value 70000 at 'token.port' is out of range for u16 (0..=65535)")
+        );
+        assert!(invalid("-128", "-129").contains("out of range for i8"));
+        assert!(invalid("8080", "-1").contains("out of range for u16"));
+        assert!(
+            invalid("supply: 3", "count: 9223372036854775808\n        supply: 3")
+                .contains("out of range for i64")
+        );
+        assert!(parse_rec("{ n: 340282366920938463463374607431768211456 }").is_err());
+        assert!(parse_rec("{ n: -9223372036854775809 }").is_err());
+        assert_eq!(
+            parse_rec("{ n: -9223372036854775808 }")
+                .unwrap()
+                .root
+                .fields["n"],
+            RecValue::Int(i64::MIN)
+        );
+    }
+
+    #[test]
+    fn test_decimals() {
+        use crate::value::{DecimalFormat, SerializeOptions};
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Fees {
+            base: Decimal,
+            priority: Decimal,
+            approx: f64,
+        }
+
+        let source = r#"
+@type Fees {
+    base: decimal(precision: 10, scale: 9)
+    priority: decimal
+    approx: decimal
+}
+
+{
+    fees: Fees {
+        base: decimal("0.000005000")
+        priority: -1.25d
+        approx: 0.1d
+    }
+}
+"#;
+        let doc = parse_rec(source).unwrap();
+        validate(&doc).unwrap();
+        let root = RecValue::Object(doc.root.clone());
+        let fees: Fees = from_value(root.get("fees").unwrap()).unwrap();
+        assert_eq!(fees.base.to_string(), "0.000005000");
+        assert_eq!((fees.base.mantissa(), fees.base.scale()), (5000, 9));
+        assert_eq!(fees.priority, Decimal::new(-125, 2).unwrap());
+        assert_eq!(fees.approx, 0.1);
+        assert_eq!(
+            to_value(&fees).unwrap().get("base"),
+            Some(&RecValue::Decimal(fees.base))
+        );
+
+        let json = serde_json::to_value(doc.serialize_with(&SerializeOptions::default())).unwrap();
+        assert_eq!(json["fees"]["base"], "0.000005000");
+        let options = SerializeOptions {
+            decimal_format: DecimalFormat::Number,
+            ..Default::default()
+        };
+        let json = serde_json::to_value(doc.serialize_with(&options)).unwrap();
+        assert_eq!(json["fees"]["priority"], -1.25);
+
+        let printed = printer::Printer::default().print_document(&doc);
+        assert!(printed.contains(r#"base: decimal(precision: 10, scale: 9)"#));
+        assert!(printed.contains(r#"priority: decimal("-1.25")"#));
+        assert_eq!(parse_rec(&printed).unwrap(), doc);
+
+        let invalid = |from: &str, to: &str| {
+            let doc = parse_rec(&source.replacen(from, to, 1)).unwrap();
+            validate(&doc).unwrap_err().to_string()
+        };
+        assert!(
+            invalid("0.000005000\"", "0.0000050001\"").contains(
+                "has 10 digits after the point; decimal(precision: 10, scale: 9) allows 9"
+            )
+        );
+        assert!(invalid("0.000005000\"", "12.5\"").contains("has 2 digits before the point"));
+        assert!(parse_rec(r#"{ n: decimal("1.2.3") }"#).is_err());
+        assert_eq!(
+            parse_rec(r#"{ n: decimal("1e5") }"#)
+                .unwrap_err()
+                .to_string(),
+            "Invalid decimal: '1e5' is not a decimal number; expected digits with an optional \
+             sign and fraction, such as \"-12.50\""
+        );
+        assert_eq!(
+            parse_rec(r#"{ n: decimal( "1.50" ), addr: socket( "1.2.3.4:80" ) }"#)
+                .unwrap()
+                .root
+                .fields,
+            parse_rec(r#"{ n: decimal("1.50"), addr: socket("1.2.3.4:80") }"#)
+                .unwrap()
+                .root
+                .fields
+        );
+        assert_eq!(
+            parse_rec("{ n: 5d }").unwrap().root.fields["n"],
+            RecValue::Duration(std::time::Duration::from_secs(5 * 86400))
+        );
+    }
+
+    #[test]
+    fn test_byte_strings() {
+        use serde::Deserialize;
+
+        #[derive(Debug, Deserialize)]
+        struct Secrets {
+            hmac_key: [u8; 4],
+            salt: Vec<u8>,
+            seed: String,
+        }
+
+        let source = r#"
+@type Secrets {
+    hmac_key: hex(4)
+    salt: bytes
+    seed: base64
+}
+
+{
+    secrets: Secrets {
+        hmac_key: hex("DEADbeef")
+        salt: base64("c2FsdA==")
+        seed: base64("AAEC")
+    }
+}
+"#;
+        let doc = parse_rec(source).unwrap();
+        validate(&doc).unwrap();
+        let root = RecValue::Object(doc.root.clone());
+        let secrets: Secrets = from_value(root.get("secrets").unwrap()).unwrap();
+        assert_eq!(secrets.hmac_key, [0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(secrets.salt, b"salt");
+        assert_eq!(secrets.seed, "AAEC");
+
+        let seed = root.get("secrets").unwrap().get("seed").unwrap();
+        assert_eq!(seed.to_bytes().unwrap(), [0, 1, 2]);
+        assert_eq!(seed.to_byte_array::<3>().unwrap(), [0, 1, 2]);
+        assert!(seed.to_byte_array::<4>().is_err());
+        assert_eq!(Vec::<u8>::from_rec(seed).unwrap(), [0, 1, 2]);
+        assert_eq!(
+            printer::Printer::default().print_value(seed),
+            r#"base64("AAEC")"#
+        );
+
+        let invalid = |from: &str, to: &str| {
+            let doc = parse_rec(&source.replacen(from, to, 1)).unwrap();
+            validate(&doc).unwrap_err().to_string()
+        };
+        assert!(
+            invalid("DEADbeef", "DEADbeef00")
+                .contains("Value at 'secrets.hmac_key' is 5 bytes long; hex(4) requires 4")
+        );
+        assert!(invalid("hex(\"DEADbeef\")", "base64(\"3q2+7w==\")").contains("expected hex(4)"));
+        assert!(invalid("AAEC", "A!EC").contains("not valid base64"));
+        assert!(invalid("DEADbeef", "DEADbee").contains("secrets.hmac_key"));
+    }
+
+    #[test]
+    fn test_key_types() {
+        let source = r#"
+@type Signers {
+    authority: pubkey(on_curve: true)
+    vault: pubkey(on_curve: false)
+    relayer: secp256k1
+    treasury: evm_address
+}
+
+{
+    signers: Signers {
+        authority: pubkey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA")
+        vault: pubkey("8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR")
+        relayer: secp256k1("0x0279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798")
+        treasury: evm_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed")
+    }
+}
+"#;
+        let doc = parse_rec(source).unwrap();
+        validate(&doc).unwrap();
+        let root = RecValue::Object(doc.root.clone());
+        let signers = root.get("signers").unwrap();
+
+        let relayer = signers.get("relayer").unwrap().to_secp256k1().unwrap();
+        assert_eq!(relayer.to_uncompressed()[0], 4);
+        assert_eq!(
+            relayer.evm_address().to_string(),
+            "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"
+        );
+        let treasury = EvmAddress::from_rec(signers.get("treasury").unwrap()).unwrap();
+        assert_eq!(
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"
+                .parse::<EvmAddress>()
+                .unwrap(),
+            treasury
+        );
+        assert_eq!(
+            printer::Printer::default().print_value(signers.get("treasury").unwrap()),
+            r#"evm_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed")"#
+        );
+
+        let invalid = |from: &str, to: &str| {
+            let doc = parse_rec(&source.replacen(from, to, 1)).unwrap();
+            validate(&doc).unwrap_err()
+        };
+        let err = invalid(
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD",
+        );
+        assert!(matches!(err, RecError::InvalidEvmAddress(_)));
+        assert!(
+            err.to_string().contains(
+                "expected 0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed at 'signers.treasury'"
+            )
+        );
+        assert!(matches!(
+            invalid("0x5aAeb", "0x5aAeb00"),
+            RecError::InvalidEvmAddress(_)
+        ));
+        assert!(matches!(
+            invalid("0x0279BE", "0x0579BE"),
+            RecError::InvalidSecp256k1Key(_)
+        ));
+        assert!(matches!(
+            invalid("0x0279BE", "0x79BE"),
+            RecError::InvalidSecp256k1Key(_)
+        ));
+        let mut uncompressed_prefix = [0u8; 33];
+        uncompressed_prefix[0] = 4;
+        assert_eq!(
+            Secp256k1Pubkey::from_sec1_bytes(&uncompressed_prefix)
+                .unwrap_err()
+                .to_string(),
+            "Invalid secp256k1 public key: a 04 prefix is for 65-byte uncompressed keys, got 33 bytes"
+        );
+        let err = invalid(
+            "8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR",
+            "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+        );
+        assert!(err.to_string().contains("at 'signers.vault' is on the ed25519 curve; pubkey(on_curve: false) requires a point off it"));
+        assert!(matches!(
+            invalid("secp256k1(\"0x", "evm_address(\"0x"),
+            RecError::ValidationError(_)
+        ));
+    }
+
+    #[test]
+    fn test_program_derived_addresses() {
+        let loader: Pubkey = "BPFLoaderUpgradeab1e11111111111111111111111"
+            .parse()
+            .unwrap();
+        let seed_key: Pubkey = "SeedPubey1111111111111111111111111111111111"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            Pubkey::create_program_address(&[b"Talking", b"Squirrels"], &loader)
+                .unwrap()
+                .to_string(),
+            "2fnQrngrQT4SeLcdToJAD96phoEjNL2man2kfRLCASVk"
+        );
+        assert_eq!(
+            Pubkey::create_program_address(&[seed_key.as_bytes(), &[1]], &loader)
+                .unwrap()
+                .to_string(),
+            "976ymqVnfE32QFe6NfGDctSvVa36LWnvYxhU6G2232YL"
+        );
+
+        let source = r#"
+@type Accounts {
+    vault: pubkey(on_curve: false)
+    escrows: [pubkey]
+}
+
+{
+    accounts: Accounts {
+        vault: pda(pubkey("BPFLoaderUpgradeab1e11111111111111111111111"), ["Talking", "Squirrels"])
+        escrows: [
+            pda("BPFLoaderUpgradeab1e11111111111111111111111", [
+                "escrow",
+                pubkey("SeedPubey1111111111111111111111111111111111"),
+                u64(7),
+                hex("beef"),
+            ])
+        ]
+    }
+}
+"#;
+        // Parsing leaves pda(..) as written, and it still checks against an
+        // off-curve pubkey field.
+        let parsed = parse_rec(source).unwrap();
+        validate(&parsed).unwrap();
+        assert!(matches!(
+            RecValue::Object(parsed.root.clone())
+                .get("accounts")
+                .unwrap()
+                .get("vault"),
+            Some(RecValue::Pda(_))
+        ));
+        assert!(parsed.derived_addresses.is_empty());
+
+        let doc = Loader::new().parse(source).unwrap();
+        validate(&doc).unwrap();
+        let [vault, escrow] = doc.derived_addresses.as_slice() else {
+            panic!("expected two derived addresses");
+        };
+        assert_eq!(vault.path, "accounts.vault");
+        assert_eq!(escrow.path, "accounts.escrows[0]");
+        assert_eq!(
+            vault.address,
+            Pubkey::create_program_address(&[b"Talking", b"Squirrels", &[vault.bump]], &loader)
+                .unwrap()
+        );
+        let seeds: [&[u8]; 5] = [
+            b"escrow",
+            seed_key.as_bytes(),
+            &7u64.to_le_bytes(),
+            &[0xbe, 0xef],
+            &[escrow.bump],
+        ];
+        assert_eq!(
+            escrow.address,
+            Pubkey::create_program_address(&seeds, &loader).unwrap()
+        );
+        let root = RecValue::Object(doc.root.clone());
+        assert_eq!(
+            root.get("accounts").unwrap().get("vault").unwrap(),
+            &RecValue::Pubkey(vault.address.to_string())
+        );
+
+        let pda = Pda {
+            program_id: loader.to_string(),
+            seeds: vec![
+                PdaSeed::String("vault".into()),
+                PdaSeed::Int(IntType::U8, 1),
+            ],
+        };
+        assert_eq!(
+            printer::Printer::default().print_value(&RecValue::Pda(pda.clone())),
+            r#"pda(pubkey("BPFLoaderUpgradeab1e11111111111111111111111"), ["vault", u8(1)])"#
+        );
+        assert_eq!(
+            RecValue::Pda(pda.clone()).to_pubkey().unwrap(),
+            pda.derive().unwrap().0
+        );
+
+        let long_seed = source.replacen("\"escrow\"", "\"an escrow seed longer than 32 bytes\"", 1);
+        let message =
+            "seed 0 is 35 bytes long; seeds are at most 32 in pda at 'accounts.escrows[0]'";
+        let err = Loader::new().parse(&long_seed).unwrap_err();
+        assert!(err.to_string().contains(message));
+        let err = validate(&parse_rec(&long_seed).unwrap()).unwrap_err();
+        assert!(err.to_string().contains(message));
+        assert!(parse_rec(&source.replacen("u64(7)", "u8(256)", 1)).is_err());
+        assert!(
+            validate(
+                &parse_rec(&source.replacen(
+                    "vault: pubkey(on_curve: false)",
+                    "vault: pubkey(on_curve: true)",
+                    1
+                ))
+                .unwrap()
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_keypair_pubkey() {
+        let dir = std::env::temp_dir().join(format!("rec-keypair-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("keys")).unwrap();
+        let secret = [7u8; 32];
+        let public = ed25519_dalek::SigningKey::from_bytes(&secret)
+            .verifying_key()
+            .to_bytes();
+        let keypair: Vec<u8> = secret.iter().chain(&public).copied().collect();
+        std::fs::write(
+            dir.join("keys/id.json"),
+            serde_json::to_string(&keypair).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("app.rec"),
+            r#"{ authority: keypair_pubkey("keys/id.json") }"#,
+        )
+        .unwrap();
+
+        let doc = Loader::new().load(dir.join("app.rec")).unwrap();
+        validate(&doc).unwrap();
+        let expected = Pubkey::new(public).to_string();
+        assert_eq!(
+            doc.root.fields["authority"],
+            RecValue::Pubkey(expected.clone())
+        );
+        let json = serde_json::to_string(&doc.root).unwrap();
+        assert_eq!(json, format!(r#"{{"authority":"{}"}}"#, expected));
+
+        let mut loader = Loader::new();
+        loader.base_dir = dir.join("keys");
+        let doc = loader
+            .parse(r#"{ signer: [keypair_pubkey("id.json")] }"#)
+            .unwrap();
+        assert_eq!(
+            doc.root.fields["signer"],
+            RecValue::Array(vec![RecValue::Pubkey(expected)])
+        );
+
+        let unread = parse_rec(
+            r#"@type Signer { key: pubkey(on_curve: true) }
+{ signer: Signer { key: keypair_pubkey("nope.json") } }"#,
+        )
+        .unwrap();
+        validate(&unread).unwrap();
+        assert_eq!(
+            unread.root.fields["signer"].get("key"),
+            Some(&RecValue::KeypairPubkey("nope.json".into()))
+        );
+
+        let unread = RecValue::KeypairPubkey("id.json".into());
+        assert!(matches!(
+            unread.to_pubkey(),
+            Err(RecError::InvalidKeypair(_))
+        ));
+        assert_eq!(
+            printer::Printer::default().print_value(&unread),
+            r#"keypair_pubkey("id.json")"#
+        );
+
+        let mut tampered = keypair.clone();
+        tampered[63] ^= 1;
+        std::fs::write(
+            dir.join("keys/id.json"),
+            serde_json::to_string(&tampered).unwrap(),
+        )
+        .unwrap();
+        let err = Loader::new().load(dir.join("app.rec")).unwrap_err();
+        assert!(matches!(err, RecError::InvalidKeypair(_)));
+        assert!(err.to_string().contains("does not match"));
+        assert!(err.to_string().ends_with("at 'authority'"));
+        assert!(!err.to_string().contains("7, 7"));
+        let err = loader
+            .parse(r#"{ k: keypair_pubkey("missing.json") }"#)
+            .unwrap_err();
+        assert!(matches!(err, RecError::InvalidKeypair(_)));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_rec_reads_nothing() {
+        let source = r#"
+@type Signer { key: pubkey }
+{
+    signer: Signer { key: keypair_pubkey("missing/id.json") }
+    home: env("HOME")
+    token: secret_ref("env", "HOME")
+}"#;
+        let doc = parse_rec(source).unwrap();
+        validate(&doc).unwrap();
+        assert_eq!(
+            doc.root.fields["signer"].get("key"),
+            Some(&RecValue::KeypairPubkey("missing/id.json".into()))
+        );
+        assert!(matches!(doc.root.fields["home"], RecValue::Env { .. }));
+        assert!(matches!(
+            doc.root.fields["token"],
+            RecValue::SecretRef { .. }
+        ));
+        assert!(Loader::new().parse(source).is_err());
+    }
+
+    #[test]
+    fn test_url_policies() {
+        let source = r#"
+@alias DbUrl = url<postgres, postgresql>
+@alias Replicas = [DbUrl]
+
+@type Services {
+    db: DbUrl
+    replicas: Replicas
+    cache: url(scheme: "redis", path: false)
+    feed: url(scheme: ["wss"], host: true)
+    site: url
+}
+
+{
+    services: Services {
+        db: url("postgres://app@db.internal:5432/app")
+        replicas: [url("postgresql://replica/app")]
+        cache: url("redis://cache:6379")
+        feed: url("wss://stream.example.com/v1")
+        site: url("https://example.com")
+    }
+    docs: url("https://docs.example.com")
+}
+"#;
+        let doc = parse_rec(source).unwrap();
+        validate(&doc).unwrap();
+        let db_url = RecType::Url(UrlPolicy {
+            schemes: vec!["postgres".into(), "postgresql".into()],
+            ..UrlPolicy::default()
+        });
+        assert_eq!(doc.type_aliases["DbUrl"], db_url);
+        assert_eq!(
+            doc.type_aliases["Replicas"],
+            RecType::Array(Box::new(db_url.clone()))
+        );
+        let fields = &doc.type_definitions["Services"].fields;
+        assert_eq!(fields["db"].ty, db_url);
+        assert_eq!(
+            fields["cache"].ty.to_string(),
+            r#"url(scheme: ["redis"], path: false)"#
+        );
+        assert!(
+            printer::Printer::default()
+                .print_document(&doc)
+                .starts_with("@alias DbUrl = url(scheme: [\"postgres\", \"postgresql\"])\n")
+        );
+
+        let invalid = |from: &str, to: &str| {
+            let doc = parse_rec(&source.replacen(from, to, 1)).unwrap();
+            validate(&doc).unwrap_err().to_string()
+        };
+        assert!(invalid("postgres://app", "mysql://app").contains(
+            "'mysql://app@db.internal:5432/app' at 'services.db': scheme 'mysql' is not one of postgres, postgresql"
+        ));
+        assert!(
+            invalid("postgresql://replica", "redis://replica").contains("services.replicas[0]")
+        );
+        assert!(invalid("6379\"", "6379/0\"").contains("does not allow a path"));
+        let hostless = parse_rec(
+            r#"@type T { sock: url(scheme: "unix", host: true) }
+            { t: T { sock: url("unix:/run/app.sock") } }"#,
+        )
+        .unwrap();
+        assert!(
+            validate(&hostless)
+                .unwrap_err()
+                .to_string()
+                .contains(r#"url(scheme: ["unix"], host: true) requires a host"#)
+        );
+        assert!(
+            invalid("https://example.com", "wss://example.com")
+                .contains("scheme 'wss' is not one of http, https")
+        );
+        assert!(invalid("https://docs", "ftp://docs").contains("at 'docs'"));
+        assert!(invalid("https://docs.example.com", "not a url").contains("Invalid URL"));
+
+        let cyclic = "@alias A = [B]\n@alias B = A\n{}";
+        assert!(
+            parse_rec(cyclic)
+                .unwrap_err()
+                .to_string()
+                .contains("Cyclic alias")
+        );
+        assert!(matches!(
+            parse_rec("@alias Services = url\n@type Services { a: int }\n{}"),
+            Err(RecError::DuplicateKey(_))
+        ));
+        assert!(parse_rec("@type T { a: url(scheme: 5) }\n{}").is_err());
+
+        let composed = parse_rec(
+            "@alias Port = u16\n@type A { port: Port }\n@type B { port: u16 }\n\
+             @type C extends A, B {}\n{}",
+        )
+        .unwrap();
+        assert_eq!(
+            composed.type_definitions["C"].fields["port"].ty,
+            RecType::SizedInt(IntType::U16)
+        );
+    }
+
+    #[test]
+    fn test_secrets() {
+        use crate::value::SerializeOptions;
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Debug, Deserialize, Serialize)]
+        struct Auth {
+            jwt_secret: Secret,
+            issuer: String,
+        }
+
+        let source = r#"
+@type Auth {
+    jwt_secret: secret
+    issuer: string
+}
+{
+    auth: Auth {
+        jwt_secret: secret("hunter2")
+        issuer: "rec"
+    }
+    extra: [secret("s3cr3t")]
+}
+"#;
+        let doc = parse_rec(source).unwrap();
+        validate(&doc).unwrap();
+        let auth = doc.root.fields.get("auth").unwrap();
+        assert!(!format!("{:?}", doc).contains("hunter2"));
+
+        let json = serde_json::to_string(&doc.root).unwrap();
+        assert!(!json.contains("hunter2") && !json.contains("s3cr3t"));
+        assert!(json.contains(r#""jwt_secret":"[REDACTED]""#));
+        let options = SerializeOptions {
+            reveal_secrets: true,
+            ..Default::default()
+        };
+        let revealed = serde_json::to_value(doc.serialize_with(&options)).unwrap();
+        assert_eq!(revealed["auth"]["jwt_secret"], "hunter2");
+        assert_eq!(revealed["extra"][0], "s3cr3t");
+
+        let parsed: Auth = from_value(auth).unwrap();
+        assert_eq!(parsed.jwt_secret.expose_secret(), "hunter2");
+        assert_eq!(parsed.issuer, "rec");
+        assert_eq!(parsed.jwt_secret.to_string(), "[REDACTED]");
+        assert_eq!(format!("{:?}", parsed.jwt_secret), "[REDACTED]");
+        assert_eq!(
+            to_value(&parsed).unwrap().get("jwt_secret"),
+            Some(&RecValue::Secret("hunter2".into()))
+        );
+        let written = to_string_pretty(&parsed).unwrap();
+        assert!(written.contains(r#"jwt_secret: secret("hunter2")"#));
+        let reread: Auth = from_str(&written).unwrap();
+        assert_eq!(reread.jwt_secret.expose_secret(), "hunter2");
+        let reread: Auth = from_value(&to_value(&parsed).unwrap()).unwrap();
+        assert_eq!(reread.jwt_secret.expose_secret(), "hunter2");
+
+        // Only `Secret` reads a secret's value.
+        let jwt_secret = auth.get("jwt_secret").unwrap();
+        assert_eq!(
+            from_value::<String>(jwt_secret).unwrap_err().to_string(),
+            "Deserialize error at <root>: secret values can only be read into rec::Secret"
+        );
+        assert!(
+            from_value::<serde_json::Value>(auth)
+                .unwrap_err()
+                .to_string()
+                .contains("at jwt_secret")
+        );
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap()["jwt_secret"],
+            "[REDACTED]"
+        );
+        assert_eq!(
+            Secret::from_rec(auth.get("jwt_secret").unwrap())
+                .unwrap()
+                .expose_secret(),
+            "hunter2"
+        );
+
+        assert!(
+            printer::Printer::default()
+                .print_value(auth)
+                .contains(r#"secret("hunter2")"#)
+        );
+
+        let err = parse_rec(
+            r#"@type Auth { jwt_secret: secret }
+{ auth: Auth { jwt_secret: "hunter2" } }"#,
+        )
+        .and_then(|doc| validate(&doc))
+        .unwrap_err();
+        assert!(err.to_string().contains("expected secret, got string"));
+        let err = RecValue::Secret("hunter2".into()).to_url().unwrap_err();
+        assert!(!err.to_string().contains("hunter2"));
+    }
+
+    #[test]
+    fn test_secret_refs() {
+        let source = r#"
+@type Auth {
+    jwt_secret: secret
+    db_password: secret
+}
+{
+    auth: Auth {
+        jwt_secret: secret_ref("vault", "jwt")
+        db_password: secret_ref("docker", "db_password")
+    }
+}
+"#;
+        let dir = std::env::temp_dir().join(format!("rec-secrets-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("db_password"), "hunter2\n").unwrap();
+        let loader = Loader::new()
+            .with_secret_provider("vault", MemoryProvider::new().with("jwt", "s3cr3t"))
+            .with_secret_provider("docker", DirProvider::new(&dir));
+
+        let doc = loader.parse(source).unwrap();
+        validate(&doc).unwrap();
+        let auth = doc.root.fields.get("auth").unwrap();
+        assert_eq!(
+            auth.get("jwt_secret")
+                .unwrap()
+                .to_secret()
+                .unwrap()
+                .expose_secret(),
+            "s3cr3t"
+        );
+        assert_eq!(
+            auth.get("db_password")
+                .unwrap()
+                .to_secret()
+                .unwrap()
+                .expose_secret(),
+            "hunter2"
+        );
+        assert!(
+            !serde_json::to_string(&doc.root)
+                .unwrap()
+                .contains("hunter2")
+        );
+
+        let unread = parse_rec(source).unwrap();
+        validate(&unread).unwrap();
+        let jwt = &unread.root.fields["auth"].get("jwt_secret").unwrap();
+        assert_eq!(
+            printer::Printer::default().print_value(jwt),
+            r#"secret_ref("vault", "jwt")"#
+        );
+        assert!(matches!(jwt.to_secret(), Err(RecError::MissingSecret(_))));
+        let home = parse_rec(r#"{ home: secret_ref("env", "HOME") }"#).unwrap();
+        assert_eq!(
+            printer::Printer::default().print_document(&home),
+            "{\n    home: secret_ref(\"env\", \"HOME\")\n}\n"
+        );
+
+        let err = Loader::new().parse(source).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Missing secret: no secret provider named 'vault' for 'jwt' at \
+             'auth.jwt_secret'; registered providers are env"
+        );
+        let err = loader
+            .parse(r#"{ token: secret_ref("vault", "token") }"#)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Missing secret: 'token' not found in provider 'vault' at 'token'"
+        );
+        let err = loader
+            .parse(r#"{ token: secret_ref("docker", "../db_password") }"#)
+            .unwrap_err();
+        assert!(matches!(err, RecError::MissingSecret(_)));
+        let err = Loader::new()
+            .parse(r#"{ token: secret_ref("env", "REC_TEST_UNSET_SECRET") }"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("not found in provider 'env'"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_secret_refs_unread() {
+        let source = r#"
+@type Auth { jwt_secret: secret }
+{ auth: Auth { jwt_secret: secret_ref("env", "PATH") } }"#;
+        let doc = parse_rec(source).unwrap();
+        validate(&doc).unwrap();
+        let jwt_secret = doc.root.fields["auth"].get("jwt_secret").unwrap();
+        assert_eq!(
+            jwt_secret,
+            &RecValue::SecretRef {
+                provider: "env".into(),
+                name: "PATH".into(),
+            }
+        );
+        assert_eq!(
+            jwt_secret.to_secret().unwrap_err().to_string(),
+            "Missing secret: 'PATH' from 'env' has not been read; parse the document with rec::Loader"
+        );
+        assert_eq!(
+            serde_json::to_value(&doc.root).unwrap()["auth"]["jwt_secret"],
+            "[REDACTED]"
+        );
+        assert!(
+            printer::Printer::default()
+                .print_document(&doc)
+                .contains(r#"jwt_secret: secret_ref("env", "PATH")"#)
+        );
+    }
+
+    #[test]
+    fn test_encrypted_values() {
+        use crate::encryption::{decrypt_source, encrypt_source};
+
+        let source = r#"@type Auth {
+    jwt_secret: secret
+}
+
+{
+    // Only secret(..) values are encrypted.
+    auth: Auth {
+        jwt_secret: secret("hunter2")  // rotated monthly
+    }
+    tokens: [secret("a"), "plain"]
+}
+"#;
+        let key = DecryptionKey::from_bytes([9; 32]);
+        let recipient: RecipientKey = key.recipient().to_string().parse().unwrap();
+        let (encrypted, count) = encrypt_source(source, &recipient).unwrap();
+        assert_eq!(count, 2);
+        assert!(!encrypted.contains("hunter2"));
+        assert!(encrypted.contains("// rotated monthly"));
+        assert!(encrypted.contains(r#""plain"]"#));
+        let (decrypted, count) = decrypt_source(&encrypted, &key).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(decrypted, source);
+
+        let doc = Loader::new()
+            .with_decryption_key(key.clone())
+            .parse(&encrypted)
+            .unwrap();
+        validate(&doc).unwrap();
+        let jwt = doc.root.fields["auth"].get("jwt_secret").unwrap();
+        assert_eq!(jwt.to_secret().unwrap().expose_secret(), "hunter2");
+
+        let doc = parse_rec(&encrypted).unwrap();
+        validate(&doc).unwrap();
+        let jwt = doc.root.fields["auth"].get("jwt_secret").unwrap();
+        assert!(matches!(jwt, RecValue::Encrypted(_)));
+        assert!(matches!(jwt.to_secret(), Err(RecError::Encryption(_))));
+        let json = serde_json::to_value(&doc.root).unwrap();
+        assert_eq!(json["auth"]["jwt_secret"], "[REDACTED]");
+
+        let other = DecryptionKey::from_bytes([10; 32]);
+        let err = Loader::new()
+            .with_decryption_key(other)
+            .parse(&encrypted)
+            .unwrap_err();
+        assert!(matches!(err, RecError::Encryption(_)));
+        assert!(err.to_string().ends_with("at 'auth.jwt_secret'"));
+
+        let payload = crate::encryption::encrypt("hunter2", &recipient).unwrap();
+        let mut tampered =
+            base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &payload).unwrap();
+        *tampered.last_mut().unwrap() ^= 1;
+        let tampered = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, tampered);
+        assert!(key.decrypt(&tampered).is_err());
+        assert_ne!(
+            payload,
+            crate::encryption::encrypt("hunter2", &recipient).unwrap()
+        );
+
+        let dir = std::env::temp_dir().join(format!("rec-encryption-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("key.txt"), key.to_file_contents().as_str()).unwrap();
+        let read = DecryptionKey::read(dir.join("key.txt")).unwrap();
+        assert_eq!(read.recipient(), key.recipient());
+        std::fs::write(dir.join("bad.txt"), "not a key\n").unwrap();
+        let err = DecryptionKey::read(dir.join("bad.txt")).unwrap_err();
+        assert!(!err.to_string().contains("not a key"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_env_values() {
+        use std::collections::HashMap;
+
+        let source = r#"
+@enum LogLevel { DEBUG, INFO }
+
+@type Server {
+    host: string
+    port: u16
+    timeout: duration
+    level: LogLevel
+    token: secret
+}
+
+{
+    server: Server {
+        host: "${HOST}"
+        port: env("PORT", 8080)
+        timeout: env("TIMEOUT", 30s)
+        level: env("LOG_LEVEL", .INFO)
+        token: env("TOKEN")
+    }
+    origin: "https://${HOST}:${PORT}"
+    workers: env("WORKERS", 4)
+    price: "$${PRICE}"
+}
+"#;
+        let env = |vars: &[(&str, &str)]| -> HashMap<String, String> {
+            vars.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        let load = |vars: &[(&str, &str)]| Loader::new().with_env(env(vars)).parse(source);
+
+        let doc = load(&[("HOST", "example.com"), ("PORT", "9000"), ("TOKEN", "t0k")]).unwrap();
+        validate(&doc).unwrap();
+        let server = &doc.root.fields["server"];
+        assert_eq!(
+            server.get("host"),
+            Some(&RecValue::String("example.com".into()))
+        );
+        assert_eq!(server.get("port"), Some(&RecValue::Int(9000)));
+        assert_eq!(
+            server.get("timeout").unwrap().to_duration().unwrap(),
+            std::time::Duration::from_secs(30)
+        );
+        assert!(matches!(
+            server.get("level"),
+            Some(RecValue::EnumVariant { enum_name, variant, .. })
+                if enum_name == "LogLevel" && variant == "INFO"
+        ));
+        assert_eq!(
+            server
+                .get("token")
+                .unwrap()
+                .to_secret()
+                .unwrap()
+                .expose_secret(),
+            "t0k"
+        );
+        assert_eq!(
+            doc.root.fields["origin"],
+            RecValue::String("https://example.com:9000".into())
+        );
+        assert_eq!(doc.root.fields["workers"], RecValue::Int(4));
+        assert_eq!(
+            doc.root.fields["price"],
+            RecValue::String("${PRICE}".into())
+        );
+
+        let doc = load(&[
+            ("HOST", "h"),
+            ("PORT", "1"),
+            ("TOKEN", "t"),
+            ("TIMEOUT", "1m"),
+            ("LOG_LEVEL", "DEBUG"),
+            ("WORKERS", "8"),
+        ])
+        .unwrap();
+        validate(&doc).unwrap();
+        let server = &doc.root.fields["server"];
+        assert_eq!(
+            server.get("timeout").unwrap().to_duration().unwrap(),
+            std::time::Duration::from_secs(60)
+        );
+        assert!(matches!(
+            server.get("level"),
+            Some(RecValue::EnumVariant { variant, .. }) if variant == "DEBUG"
+        ));
+        assert_eq!(doc.root.fields["workers"], RecValue::Int(8));
+
+        // Overrides the base variables; an empty value unsets one.
+        let base = [("HOST", "h"), ("PORT", "1"), ("TOKEN", "t")];
+        let error = |extra: &[(&str, &str)]| {
+            let mut vars = base.to_vec();
+            vars.retain(|(k, _)| !extra.iter().any(|(e, _)| e == k));
+            vars.extend(extra.iter().filter(|(_, v)| !v.is_empty()));
+            load(&vars).unwrap_err().to_string()
+        };
+        assert_eq!(
+            error(&[("TOKEN", "")]),
+            "Environment variable error: TOKEN is not set, needed by 'server.token'"
+        );
+        assert_eq!(
+            error(&[("PORT", "abc")]),
+            "Environment variable error: PORT at 'server.port' is not a valid u16"
+        );
+        assert!(error(&[("PORT", "70000")]).contains("out of range for u16"));
+        assert!(error(&[("LOG_LEVEL", "TRACE")]).contains("LOG_LEVEL at 'server.level'"));
+        assert_eq!(
+            error(&[("WORKERS", "many")]),
+            "Environment variable error: WORKERS at 'workers' is not a valid int"
+        );
+
+        let unread = parser::parse_document(source).unwrap();
+        let printer = printer::Printer::default();
+        let server = &unread.root.fields["server"];
+        assert_eq!(
+            printer.print_value(server.get("port").unwrap()),
+            r#"env("PORT", 8080)"#
+        );
+        assert_eq!(
+            printer.print_value(server.get("host").unwrap()),
+            r#""${HOST}""#
+        );
+        assert_eq!(
+            printer.print_value(&unread.root.fields["price"]),
+            r#""$${PRICE}""#
+        );
+        let doc = parse_rec(source).unwrap();
+        validate(&doc).unwrap();
+        assert_eq!(doc, unread);
+        assert_eq!(
+            parse_rec(r#"{ greeting: "cost is ${PRICE}" }"#)
+                .unwrap()
+                .root
+                .fields["greeting"],
+            RecValue::Template("cost is ${PRICE}".into())
+        );
+        let mismatch = parse_rec(&source.replace("env(\"PORT\", 8080)", "env(\"PORT\", \"x\")"));
+        assert!(validate(&mismatch.unwrap()).is_err());
+
+        let doc = Loader::new()
+            .with_env(env(&[("JWT", "s3cr3t")]))
+            .parse(r#"{ jwt: secret_ref("env", "JWT") }"#)
+            .unwrap();
+        assert_eq!(
+            doc.root.fields["jwt"].to_secret().unwrap().expose_secret(),
+            "s3cr3t"
+        );
+    }
+
+    #[test]
+    fn test_env_values_unread() {
+        use std::collections::HashMap;
+
+        let source = r#"
+@type Limits { ports: [int] }
+{
+    limits: Limits { ports: env("PORTS", [80]) }
+    greeting: "hello ${USER}"
+    price: "$${PRICE}"
+    total: "$${PRICE} is ${PRICE}"
+}"#;
+        let unread = parse_rec(source).unwrap();
+        validate(&unread).unwrap();
+        assert_eq!(
+            serde_json::to_value(&unread.root).unwrap(),
+            serde_json::json!({
+                "limits": { "ports": "env(\"PORTS\", [80])" },
+                "greeting": "hello ${USER}",
+                "price": "${PRICE}",
+                "total": "$${PRICE} is ${PRICE}",
+            })
+        );
+
+        let load = |ports: &str| {
+            let vars: HashMap<String, String> = [("PORTS", ports), ("USER", "ann"), ("PRICE", "5")]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            Loader::new().with_env(vars).parse(source)
+        };
+        let doc = load("[8080, 8443]").unwrap();
+        assert_eq!(
+            serde_json::to_value(&doc.root).unwrap(),
+            serde_json::json!({
+                "limits": { "ports": [8080, 8443] },
+                "greeting": "hello ann",
+                "price": "${PRICE}",
+                "total": "${PRICE} is 5",
+            })
+        );
+
+        // Variables hold literals only.
+        for ports in [
+            r#"env("PORTS")"#,
+            r#"[env("PORTS")]"#,
+            r#"["${USER}"]"#,
+            r#"[keypair_pubkey("id.json")]"#,
+            r#"[secret_ref("env", "USER")]"#,
+        ] {
+            assert_eq!(
+                load(ports).unwrap_err().to_string(),
+                "Environment variable error: PORTS at 'limits.ports' is not a valid [int]"
+            );
+        }
+    }
 }
@@ -0,0 +1,263 @@
+//! Parsing documents whose values depend on their surroundings: files next
+//! to the config (`keypair_pubkey("id.json")`), secret providers
+//! (`secret_ref("env", "JWT_SECRET")`), a decryption key
+//! (`encrypted("..")`) and environment variables (`env("PORT", 8080)`,
+//! `"${HOST}"`).
+
+use crate::env::{self, EnvSource, ProcessEnv};
+use crate::parser::parse_document;
+use crate::validator::{
+    check_value, derive_addresses, for_each_typed_value_mut, for_each_value_mut, resolve,
+};
+use crate::{
+    DecryptionKey, EnvProvider, Pubkey, RecDocument, RecError, RecValue, Secret, SecretProvider,
+};
+use ed25519_dalek::SigningKey;
+use indexmap::IndexMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use zeroize::Zeroizing;
+
+/// Parses documents and evaluates their `env(..)`, `"${..}"`,
+/// `keypair_pubkey(..)`, `pda(..)`, `secret_ref(..)` and, given a key,
+/// `encrypted(..)` values.
+///
+/// ```rust,no_run
+/// # fn main() -> Result<(), rec::RecError> {
+/// let doc = rec::Loader::new()
+///     .with_secret_provider("docker", rec::DirProvider::new("/run/secrets"))
+///     .load("config/app.rec")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Loader {
+    /// The directory relative paths in the document are resolved against.
+    /// [`Loader::load`] uses the directory of the file instead.
+    pub base_dir: PathBuf,
+    secret_providers: IndexMap<String, Arc<dyn SecretProvider>>,
+    decryption_key: Option<DecryptionKey>,
+    env: Arc<dyn EnvSource>,
+}
+
+impl Loader {
+    /// A loader that resolves paths against the current directory and reads
+    /// `secret_ref("env", ..)` values from environment variables.
+    pub fn new() -> Self {
+        Self {
+            base_dir: PathBuf::from("."),
+            secret_providers: IndexMap::new(),
+            decryption_key: None,
+            env: Arc::new(ProcessEnv),
+        }
+        .with_secret_provider("env", EnvProvider)
+    }
+
+    /// Reads `secret_ref(name, ..)` values from `provider`, replacing any
+    /// provider already registered under `name`.
+    pub fn with_secret_provider(
+        mut self,
+        name: impl Into<String>,
+        provider: impl SecretProvider + 'static,
+    ) -> Self {
+        self.secret_providers
+            .insert(name.into(), Arc::new(provider));
+        self
+    }
+
+    /// Reads `env(..)` values, `${NAME}` references and
+    /// `secret_ref("env", ..)` values from `source` instead of the process
+    /// environment.
+    pub fn with_env(self, source: impl EnvSource + 'static) -> Self {
+        let source: Arc<dyn EnvSource> = Arc::new(source);
+        let mut loader = self.with_secret_provider("env", EnvSecrets(source.clone()));
+        loader.env = source;
+        loader
+    }
+
+    /// Decrypts `encrypted(..)` values with `key`. Without a key they are
+    /// left encrypted, and reading them as secrets fails.
+    pub fn with_decryption_key(mut self, key: DecryptionKey) -> Self {
+        self.decryption_key = Some(key);
+        self
+    }
+
+    pub fn parse(&self, source: &str) -> Result<RecDocument, RecError> {
+        let mut doc = parse_document(source)?;
+        self.evaluate(&mut doc, &self.base_dir)?;
+        Ok(doc)
+    }
+
+    /// Reads and parses the file at `path`, resolving relative paths in it
+    /// against the file's directory.
+    pub fn load(&self, path: impl AsRef<Path>) -> Result<RecDocument, RecError> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)
+            .map_err(|e| RecError::ParseError(format!("Cannot read {}: {}", path.display(), e)))?;
+        let mut doc = parse_document(&source)?;
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => self.base_dir.clone(),
+        };
+        self.evaluate(&mut doc, &dir)?;
+        Ok(doc)
+    }
+
+    fn evaluate(&self, doc: &mut RecDocument, dir: &Path) -> Result<(), RecError> {
+        self.read_env(doc)?;
+        for_each_value_mut(&mut doc.root, &mut |value, path| {
+            if let RecValue::KeypairPubkey(file) = value {
+                let pubkey = read_keypair_pubkey(&dir.join(&*file)).map_err(|e| match e {
+                    RecError::InvalidKeypair(message) => {
+                        RecError::InvalidKeypair(format!("{} at '{}'", message, path))
+                    }
+                    e => e,
+                })?;
+                *value = RecValue::Pubkey(pubkey.to_string());
+            }
+            if let RecValue::SecretRef { provider, name } = value {
+                *value = RecValue::Secret(self.read_secret(provider, name, path)?);
+            }
+            if let (RecValue::Encrypted(payload), Some(key)) = (&*value, &self.decryption_key) {
+                let secret = key.decrypt(payload).map_err(|e| match e {
+                    RecError::Encryption(message) => {
+                        RecError::Encryption(format!("{} at '{}'", message, path))
+                    }
+                    e => e,
+                })?;
+                *value = RecValue::Secret(secret);
+            }
+            Ok(())
+        })?;
+        derive_addresses(doc)
+    }
+
+    /// Replaces `env(..)` values and strings with `${..}` references with
+    /// values of the declared type, then validates what was read.
+    fn read_env(&self, doc: &mut RecDocument) -> Result<(), RecError> {
+        let mut read = Vec::new();
+        for_each_typed_value_mut(doc, &mut |value, ty, path| {
+            let (text, source, inferred) = match value {
+                RecValue::Env { name, default } => match self.env.var(name) {
+                    Some(text) => {
+                        let inferred = default.as_deref().and_then(env::type_of_default);
+                        (text, name.clone(), inferred)
+                    }
+                    None => match default.take() {
+                        Some(default) => {
+                            *value = *default;
+                            return Ok(());
+                        }
+                        None => return Err(not_set(name, path)),
+                    },
+                },
+                RecValue::Template(template) => {
+                    let text = env::interpolate(template, &*self.env)
+                        .map_err(|name| not_set(&name, path))?;
+                    (text, format!("\"{}\"", template), None)
+                }
+                _ => return Ok(()),
+            };
+            // Untyped `env(..)` values take the type of their default.
+            let ty = ty.or(inferred.as_ref().map(|ty| (ty, false)));
+            let coerced = env::coerce(&text, ty).ok_or_else(|| {
+                RecError::EnvVar(format!(
+                    "{} at '{}' is not a valid {}",
+                    source,
+                    path,
+                    ty.map(|(ty, _)| ty.to_string()).unwrap_or_default()
+                ))
+            })?;
+            if let Some((ty, _)) = ty {
+                read.push((source, path.to_string(), ty.clone(), coerced.clone()));
+            }
+            *value = coerced;
+            Ok(())
+        })?;
+        resolve(doc);
+        for (source, path, ty, value) in read {
+            check_value(&value, &ty, doc, &path)
+                .map_err(|e| RecError::EnvVar(format!("{} at '{}': {}", source, path, e)))?;
+        }
+        Ok(())
+    }
+
+    fn read_secret(&self, provider: &str, name: &str, path: &str) -> Result<Secret, RecError> {
+        let Some(source) = self.secret_providers.get(provider) else {
+            let known: Vec<_> = self.secret_providers.keys().map(|k| k.as_str()).collect();
+            return Err(RecError::MissingSecret(format!(
+                "no secret provider named '{}' for '{}' at '{}'; registered providers are {}",
+                provider,
+                name,
+                path,
+                known.join(", ")
+            )));
+        };
+        match source.get(name) {
+            Ok(Some(secret)) => Ok(secret),
+            Ok(None) => Err(RecError::MissingSecret(format!(
+                "'{}' not found in provider '{}' at '{}'",
+                name, provider, path
+            ))),
+            Err(RecError::MissingSecret(message)) => Err(RecError::MissingSecret(format!(
+                "{} at '{}'",
+                message, path
+            ))),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+fn not_set(name: &str, path: &str) -> RecError {
+    RecError::EnvVar(format!("{} is not set, needed by '{}'", name, path))
+}
+
+/// Serves `secret_ref("env", ..)` from the loader's environment source.
+struct EnvSecrets(Arc<dyn EnvSource>);
+
+impl SecretProvider for EnvSecrets {
+    fn get(&self, name: &str) -> Result<Option<Secret>, RecError> {
+        Ok(self.0.var(name).map(Secret::new))
+    }
+}
+
+impl fmt::Debug for Loader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Loader")
+            .field("base_dir", &self.base_dir)
+            .field("secret_providers", &self.secret_providers.keys())
+            .field("decryption_key", &self.decryption_key)
+            .finish()
+    }
+}
+
+impl Default for Loader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reads a Solana keypair file, a JSON array of the 32-byte secret key
+/// followed by the 32-byte public key, and derives the public key from the
+/// secret half. Errors never include the key bytes.
+pub fn read_keypair_pubkey(path: &Path) -> Result<Pubkey, RecError> {
+    let invalid =
+        |reason: &str| RecError::InvalidKeypair(format!("{}: {}", path.display(), reason));
+    let text = Zeroizing::new(fs::read_to_string(path).map_err(|e| invalid(&e.to_string()))?);
+    let bytes: Zeroizing<Vec<u8>> = Zeroizing::new(
+        serde_json::from_str(&text).map_err(|_| invalid("expected a JSON array of 64 bytes"))?,
+    );
+    if bytes.len() != 64 {
+        return Err(invalid(&format!("expected 64 bytes, got {}", bytes.len())));
+    }
+    let secret: &[u8; 32] = bytes[..32].try_into().unwrap();
+    let pubkey = Pubkey::new(SigningKey::from_bytes(secret).verifying_key().to_bytes());
+    if pubkey.as_bytes()[..] != bytes[32..] {
+        return Err(invalid(
+            "the public half does not match the key derived from the secret half",
+        ));
+    }
+    Ok(pubkey)
+}
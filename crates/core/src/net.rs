@@ -0,0 +1,202 @@
+//! The address types behind `cidr(..)` and `mac(..)` values. `ip(..)` values
+//! map straight to `std::net::IpAddr`.
+
+use crate::RecError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// An IPv4 or IPv6 network written as `address/prefix`, such as
+/// `10.0.0.0/8` or `fd00::/8`. The host bits of the address must be zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Cidr {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl Cidr {
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Result<Self, RecError> {
+        let max = Self::max_prefix_len(addr);
+        if prefix_len > max {
+            return Err(RecError::InvalidCidr(format!(
+                "prefix length {} is longer than {} bits",
+                prefix_len, max
+            )));
+        }
+        let cidr = Self { addr, prefix_len };
+        if cidr.network() != addr {
+            return Err(RecError::InvalidCidr(format!(
+                "host bits are set; the network is {}/{}",
+                cidr.network(),
+                prefix_len
+            )));
+        }
+        Ok(cidr)
+    }
+
+    /// The network address.
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// The first address of the network, with the host bits cleared. The
+    /// same as [`addr`](Self::addr) for a `Cidr` built by [`Cidr::new`].
+    pub fn network(&self) -> IpAddr {
+        match self.addr {
+            IpAddr::V4(addr) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - self.prefix_len as u32)
+                    .unwrap_or(0);
+                IpAddr::V4((u32::from(addr) & mask).into())
+            }
+            IpAddr::V6(addr) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - self.prefix_len as u32)
+                    .unwrap_or(0);
+                IpAddr::V6((u128::from(addr) & mask).into())
+            }
+        }
+    }
+
+    /// Whether `ip` is in this network. Addresses of the other family never
+    /// are.
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(_), IpAddr::V4(_)) | (IpAddr::V6(_), IpAddr::V6(_)) => {
+                Self {
+                    addr: *ip,
+                    prefix_len: self.prefix_len,
+                }
+                .network()
+                    == self.network()
+            }
+            _ => false,
+        }
+    }
+
+    fn max_prefix_len(addr: IpAddr) -> u8 {
+        if addr.is_ipv4() { 32 } else { 128 }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = RecError;
+
+    fn from_str(s: &str) -> Result<Self, RecError> {
+        let (addr, prefix_len) = s
+            .split_once('/')
+            .ok_or_else(|| RecError::InvalidCidr(format!("{}: expected address/prefix", s)))?;
+        let addr: IpAddr = addr.parse().map_err(|_| {
+            RecError::InvalidCidr(format!("{}: '{}' is not an IP address", s, addr))
+        })?;
+        let prefix_len = prefix_len.parse().map_err(|_| {
+            RecError::InvalidCidr(format!(
+                "{}: prefix length '{}' is not a number",
+                s, prefix_len
+            ))
+        })?;
+        Self::new(addr, prefix_len).map_err(|e| match e {
+            RecError::InvalidCidr(message) => RecError::InvalidCidr(format!("{}: {}", s, message)),
+            e => e,
+        })
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+/// A 48-bit hardware address, written as six hex pairs separated by `:` or
+/// `-`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MacAddr([u8; 6]);
+
+impl MacAddr {
+    pub const fn new(bytes: [u8; 6]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 6] {
+        &self.0
+    }
+
+    pub fn to_bytes(self) -> [u8; 6] {
+        self.0
+    }
+}
+
+impl FromStr for MacAddr {
+    type Err = RecError;
+
+    fn from_str(s: &str) -> Result<Self, RecError> {
+        let invalid = || {
+            RecError::InvalidMac(format!(
+                "{}: expected six hex pairs, as in 00:1a:2b:3c:4d:5e",
+                s
+            ))
+        };
+        let separator = if s.contains('-') { '-' } else { ':' };
+        let mut bytes = [0; 6];
+        let mut parts = s.split(separator);
+        for byte in &mut bytes {
+            let part = parts.next().ok_or_else(invalid)?;
+            if part.len() != 2 {
+                return Err(invalid());
+            }
+            *byte = u8::from_str_radix(part, 16).map_err(|_| invalid())?;
+        }
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(Self(bytes))
+    }
+}
+
+impl From<[u8; 6]> for MacAddr {
+    fn from(bytes: [u8; 6]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d, e, g] = self.0;
+        write!(
+            f,
+            "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+            a, b, c, d, e, g
+        )
+    }
+}
+
+impl fmt::Debug for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MacAddr({})", self)
+    }
+}
+
+macro_rules! impl_serde_as_str {
+    ($($ty:ty),*) => {$(
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+                s.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    )*};
+}
+
+impl_serde_as_str!(Cidr, MacAddr);
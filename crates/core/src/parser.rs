@@ -1,74 +1,363 @@
+use crate::env;
+use crate::units;
+use crate::validator::resolve;
 use crate::{
-    EnumDef, EnumVariant, EnumVariantData, FieldDef, RecDocument, RecError, RecObject, RecType,
-    RecValue, TypeDef,
+    ByteEncoding, DecimalLimits, EnumDef, EnumDiscriminant, EnumVariant, EnumVariantData, FieldDef,
+    IntType, Pda, PdaSeed, RecDocument, RecError, RecObject, RecType, RecValue, TimeBounds,
+    TypeDef, UrlPolicy,
 };
 use indexmap::IndexMap;
 use nom::{
     IResult, Parser,
     branch::alt,
-    bytes::complete::{tag, take_while},
-    character::complete::{alpha1, alphanumeric1, char, digit1, multispace0},
-    combinator::{map, opt, recognize, value},
-    multi::{many0, separated_list0},
-    sequence::{delimited, pair},
+    bytes::complete::{is_not, tag, take_until, take_while},
+    character::complete::{alpha1, alphanumeric0, alphanumeric1, char, digit1, multispace1},
+    combinator::{map, not, opt, peek, recognize, value},
+    multi::{many0, separated_list0, separated_list1},
+    sequence::{delimited, pair, preceded, terminated},
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+/// Parses a document. Values that a [`Loader`](crate::Loader) evaluates,
+/// such as `pda(..)`, `keypair_pubkey(..)`, `secret_ref(..)` and `env(..)`,
+/// are left as written.
 pub fn parse_rec(input: &str) -> Result<RecDocument, RecError> {
+    parse_document(input)
+}
+
+/// Parses a document without evaluating any of its values.
+pub(crate) fn parse_document(input: &str) -> Result<RecDocument, RecError> {
     match document(input) {
-        Ok((_, doc)) => Ok(doc),
-        Err(e) => Err(RecError::ParseError(format!("{:?}", e))),
+        Ok(("", (doc, raw_types, raw_aliases))) => {
+            let mut doc = doc;
+            doc.type_aliases = resolve_aliases(raw_aliases, &raw_types, &doc)?;
+            doc.type_definitions =
+                compose_types(raw_types, &doc.enum_definitions, &doc.type_aliases)?;
+            expand_aliases(&mut doc);
+            resolve(&mut doc);
+            Ok(doc)
+        }
+        Ok((rest, _)) => Err(RecError::ParseError(format!(
+            "Unexpected trailing input: {}",
+            rest.lines().next().unwrap_or_default()
+        ))),
+        Err(e) => Err(parse_error(e)),
     }
 }
 
-fn document(input: &str) -> IResult<&str, RecDocument> {
+/// A `@type` definition as written, before its bases and spreads are
+/// composed into a flat field list.
+struct RawTypeDef {
+    name: String,
+    extends: Vec<String>,
+    members: Vec<TypeMember>,
+}
+
+/// A member of a `@type` body: a field declared on the type itself or a
+/// `...Base` spread.
+enum TypeMember {
+    Field(String, FieldDef),
+    Spread(String),
+}
+
+/// A `@enum`, `@type` or `@alias` definition.
+enum Definition {
+    Enum(EnumDef),
+    Type(RawTypeDef),
+    Alias(String, RecType),
+}
+
+type Document = (RecDocument, Vec<RawTypeDef>, Vec<(String, RecType)>);
+
+fn document(input: &str) -> IResult<&str, Document> {
     let (input, includes) = many0(include_statement).parse(input)?;
-    let (input, _) = multispace0(input)?;
-    let (input, enums) = many0(ws(enum_definition)).parse(input)?;
-    let (input, _) = multispace0(input)?;
-    let (input, types) = many0(ws(type_definition)).parse(input)?;
-    let (input, _) = multispace0(input)?;
+    let (input, _) = sp(input)?;
+    let (input, definitions) = many0(ws(alt((
+        map(enum_definition, Definition::Enum),
+        map(type_definition, Definition::Type),
+        map(alias_definition, |(name, ty)| Definition::Alias(name, ty)),
+    ))))
+    .parse(input)?;
+    let (input, _) = sp(input)?;
     let (input, root) = object.parse(input)?;
+    let (input, _) = sp(input)?;
 
-    let mut enum_map = HashMap::new();
-    for e in enums {
-        enum_map.insert(e.name.clone(), e);
-    }
-
-    let mut type_map = HashMap::new();
-    for t in types {
-        type_map.insert(t.name.clone(), t);
+    let mut enum_map = IndexMap::new();
+    let mut types = Vec::new();
+    let mut aliases = Vec::new();
+    for definition in definitions {
+        match definition {
+            Definition::Enum(e) => {
+                enum_map.insert(e.name.clone(), e);
+            }
+            Definition::Type(t) => types.push(t),
+            Definition::Alias(name, ty) => aliases.push((name, ty)),
+        }
     }
 
     Ok((
         input,
-        RecDocument {
-            includes,
-            type_definitions: type_map,
-            enum_definitions: enum_map,
-            root,
-        },
+        (
+            RecDocument {
+                includes,
+                type_definitions: IndexMap::new(),
+                enum_definitions: enum_map,
+                type_aliases: IndexMap::new(),
+                root,
+                derived_addresses: Vec::new(),
+            },
+            types,
+            aliases,
+        ),
     ))
 }
 
+/// Expands aliases that name other aliases, so each maps to a type without
+/// any.
+fn resolve_aliases(
+    raw: Vec<(String, RecType)>,
+    types: &[RawTypeDef],
+    doc: &RecDocument,
+) -> Result<IndexMap<String, RecType>, RecError> {
+    let mut aliases = IndexMap::new();
+    for (name, ty) in raw {
+        if aliases.contains_key(&name)
+            || types.iter().any(|t| t.name == name)
+            || doc.enum_definitions.contains_key(&name)
+        {
+            return Err(RecError::DuplicateKey(format!("@alias {}", name)));
+        }
+        aliases.insert(name, ty);
+    }
+    let mut resolved = IndexMap::new();
+    for name in aliases.keys() {
+        let ty = expand_alias(name, &aliases, &mut vec![name.clone()])?;
+        resolved.insert(name.clone(), ty);
+    }
+    Ok(resolved)
+}
+
+fn expand_alias(
+    name: &str,
+    aliases: &IndexMap<String, RecType>,
+    stack: &mut Vec<String>,
+) -> Result<RecType, RecError> {
+    let mut failed = None;
+    let ty = map_named_types(&aliases[name], &mut |inner| {
+        if failed.is_some() || !aliases.contains_key(inner) {
+            return None;
+        }
+        if stack.iter().any(|n| n == inner) {
+            stack.push(inner.to_string());
+            failed = Some(RecError::ValidationError(format!(
+                "Cyclic alias: {}",
+                stack.join(" -> ")
+            )));
+            return None;
+        }
+        stack.push(inner.to_string());
+        let expanded = expand_alias(inner, aliases, stack);
+        stack.pop();
+        expanded.map_err(|e| failed = Some(e)).ok()
+    });
+    match failed {
+        Some(e) => Err(e),
+        None => Ok(ty),
+    }
+}
+
+/// Replaces alias names in enum variant types with the aliased types.
+/// [`compose_types`] does the same for type fields.
+fn expand_aliases(doc: &mut RecDocument) {
+    if doc.type_aliases.is_empty() {
+        return;
+    }
+    let aliases = &doc.type_aliases;
+    let mut expand = |name: &str| aliases.get(name).cloned();
+    for def in doc.enum_definitions.values_mut() {
+        for variant in &mut def.variants {
+            match variant {
+                EnumVariant::Tuple(_, types) => {
+                    for ty in types {
+                        *ty = map_named_types(ty, &mut expand);
+                    }
+                }
+                EnumVariant::Struct(_, fields) => {
+                    for field in fields.values_mut() {
+                        field.ty = map_named_types(&field.ty, &mut expand);
+                    }
+                }
+                EnumVariant::Unit(..) => {}
+            }
+        }
+    }
+}
+
+/// Rebuilds `ty`, replacing each named type for which `f` returns a type.
+fn map_named_types(ty: &RecType, f: &mut dyn FnMut(&str) -> Option<RecType>) -> RecType {
+    match ty {
+        RecType::Object(name) | RecType::Enum(name) => f(name).unwrap_or_else(|| ty.clone()),
+        RecType::Array(inner) => RecType::Array(Box::new(map_named_types(inner, f))),
+        RecType::FixedArray(inner, len) => {
+            RecType::FixedArray(Box::new(map_named_types(inner, f)), *len)
+        }
+        RecType::Tuple(types) => {
+            RecType::Tuple(types.iter().map(|t| map_named_types(t, f)).collect())
+        }
+        _ => ty.clone(),
+    }
+}
+
+/// Flattens `extends` clauses and `...Base` spreads into each type's field
+/// list, with alias names replaced by the aliased types.
+///
+/// Bases are applied in order, followed by the type's own body. A field may
+/// appear more than once only with the same type; a redeclaration may change
+/// whether the field is optional, and the last one wins.
+fn compose_types(
+    raw_types: Vec<RawTypeDef>,
+    enums: &IndexMap<String, EnumDef>,
+    aliases: &IndexMap<String, RecType>,
+) -> Result<IndexMap<String, TypeDef>, RecError> {
+    let mut raw = IndexMap::new();
+    for t in raw_types {
+        if raw.contains_key(&t.name) {
+            return Err(RecError::DuplicateKey(format!("@type {}", t.name)));
+        }
+        raw.insert(t.name.clone(), t);
+    }
+
+    let mut composed = HashMap::new();
+    for name in raw.keys() {
+        compose_type(name, &raw, enums, aliases, &mut composed, &mut Vec::new())?;
+    }
+    // Keep declaration order rather than the order composition finished in.
+    Ok(raw
+        .into_keys()
+        .map(|name| {
+            let def = composed.remove(&name).expect("every type was composed");
+            (name, def)
+        })
+        .collect())
+}
+
+fn compose_type(
+    name: &str,
+    raw: &IndexMap<String, RawTypeDef>,
+    enums: &IndexMap<String, EnumDef>,
+    aliases: &IndexMap<String, RecType>,
+    composed: &mut HashMap<String, TypeDef>,
+    stack: &mut Vec<String>,
+) -> Result<(), RecError> {
+    if composed.contains_key(name) {
+        return Ok(());
+    }
+    if stack.iter().any(|n| n == name) {
+        stack.push(name.to_string());
+        return Err(RecError::ValidationError(format!(
+            "Cyclic type composition: {}",
+            stack.join(" -> ")
+        )));
+    }
+    let Some(def) = raw.get(name) else {
+        if enums.contains_key(name) {
+            return Err(RecError::ValidationError(format!(
+                "Cannot compose enum {} into a type",
+                name
+            )));
+        }
+        return Err(RecError::UnknownType(name.to_string()));
+    };
+
+    stack.push(name.to_string());
+
+    let mut fields: IndexMap<String, FieldDef> = IndexMap::new();
+    let mut own = HashSet::new();
+    let bases = def.extends.iter().map(|b| (b, None));
+    let body = def.members.iter().map(|m| match m {
+        TypeMember::Spread(b) => (b, None),
+        TypeMember::Field(f, fdef) => (f, Some(fdef)),
+    });
+
+    for (member, field) in bases.chain(body) {
+        match field {
+            None => {
+                compose_type(member, raw, enums, aliases, composed, stack)?;
+                for (fname, fdef) in &composed[member.as_str()].fields {
+                    merge_field(name, &mut fields, fname, fdef, member)?;
+                }
+            }
+            Some(fdef) => {
+                if !own.insert(member.as_str()) {
+                    return Err(RecError::DuplicateKey(format!("{}.{}", name, member)));
+                }
+                let fdef = FieldDef {
+                    ty: map_named_types(&fdef.ty, &mut |n| aliases.get(n).cloned()),
+                    ..fdef.clone()
+                };
+                merge_field(name, &mut fields, member, &fdef, name)?;
+            }
+        }
+    }
+
+    stack.pop();
+    composed.insert(
+        name.to_string(),
+        TypeDef {
+            name: name.to_string(),
+            bases: def
+                .extends
+                .iter()
+                .chain(def.members.iter().filter_map(|m| match m {
+                    TypeMember::Spread(b) => Some(b),
+                    TypeMember::Field(..) => None,
+                }))
+                .cloned()
+                .collect(),
+            fields,
+        },
+    );
+    Ok(())
+}
+
+fn merge_field(
+    type_name: &str,
+    fields: &mut IndexMap<String, FieldDef>,
+    field: &str,
+    def: &FieldDef,
+    source: &str,
+) -> Result<(), RecError> {
+    if let Some(existing) = fields.get(field)
+        && existing.ty != def.ty
+    {
+        return Err(RecError::ConflictingFieldType {
+            type_name: type_name.to_string(),
+            field: field.to_string(),
+            existing: existing.ty.to_string(),
+            incoming: format!("{} (from {})", def.ty, source),
+        });
+    }
+    fields.insert(field.to_string(), def.clone());
+    Ok(())
+}
+
 fn include_statement(input: &str) -> IResult<&str, String> {
-    let (input, _) = multispace0(input)?;
+    let (input, _) = sp(input)?;
     let (input, _) = tag("#include")(input)?;
-    let (input, _) = multispace0(input)?;
+    let (input, _) = sp(input)?;
     let (input, path) = string_literal(input)?;
-    let (input, _) = multispace0(input)?;
+    let (input, _) = sp(input)?;
     Ok((input, path))
 }
 
 fn enum_definition(input: &str) -> IResult<&str, EnumDef> {
-    let (input, _) = multispace0(input)?;
+    let (input, _) = sp(input)?;
     let (input, _) = tag("@enum")(input)?;
-    let (input, _) = multispace0(input)?;
+    let (input, _) = sp(input)?;
     let (input, name) = identifier(input)?;
-    let (input, _) = multispace0(input)?;
+    let (input, _) = sp(input)?;
     let (input, _) = char('{')(input)?;
-    let (input, variants) = separated_list0(ws(char(',')), enum_variant_def).parse(input)?;
-    let (input, _) = opt(char(',')).parse(input)?;
+    let (input, variants) = many0(terminated(ws(enum_variant_def), opt(char(',')))).parse(input)?;
     let (input, _) = ws(char('}')).parse(input)?;
 
     Ok((
@@ -81,8 +370,9 @@ fn enum_definition(input: &str) -> IResult<&str, EnumDef> {
 }
 
 fn enum_variant_def(input: &str) -> IResult<&str, EnumVariant> {
-    let (input, _) = multispace0(input)?;
+    let (input, _) = sp(input)?;
     let (input, name) = identifier(input)?;
+    let (input, _) = sp(input)?;
 
     if let Ok((input2, _)) = char::<&str, nom::error::Error<&str>>('{')(input) {
         let (input2, fields) = many0(field_definition).parse(input2)?;
@@ -95,45 +385,86 @@ fn enum_variant_def(input: &str) -> IResult<&str, EnumVariant> {
     }
 
     if let Ok((input2, _)) = char::<&str, nom::error::Error<&str>>('(')(input) {
-        let (input2, types) = separated_list0(ws(char(',')), type_expr).parse(input2)?;
+        let (input2, types) = separated_list0(ws(char(',')), ws(type_expr)).parse(input2)?;
         let (input2, _) = char(')')(input2)?;
         return Ok((input2, EnumVariant::Tuple(name.to_string(), types)));
     }
 
-    Ok((input, EnumVariant::Unit(name.to_string())))
+    let (input, discriminant) = opt(preceded(
+        ws(char('=')),
+        alt((
+            map(integer, EnumDiscriminant::Int),
+            map(string_literal, EnumDiscriminant::String),
+        )),
+    ))
+    .parse(input)?;
+
+    Ok((input, EnumVariant::Unit(name.to_string(), discriminant)))
 }
 
-fn type_definition(input: &str) -> IResult<&str, TypeDef> {
-    let (input, _) = multispace0(input)?;
+/// `@alias Name = type`.
+fn alias_definition(input: &str) -> IResult<&str, (String, RecType)> {
+    let (input, _) = sp(input)?;
+    let (input, _) = keyword("@alias").parse(input)?;
+    let (input, _) = sp(input)?;
+    let (input, name) = identifier(input)?;
+    let (input, _) = ws(char('=')).parse(input)?;
+    let (input, ty) = type_expr(input)?;
+    Ok((input, (name.to_string(), ty)))
+}
+
+fn type_definition(input: &str) -> IResult<&str, RawTypeDef> {
+    let (input, _) = sp(input)?;
     let (input, _) = tag("@type")(input)?;
-    let (input, _) = multispace0(input)?;
+    let (input, _) = sp(input)?;
     let (input, name) = identifier(input)?;
-    let (input, _) = multispace0(input)?;
+    let (input, _) = sp(input)?;
+    let (input, extends) = opt(preceded(
+        keyword("extends"),
+        separated_list0(ws(char(',')), ws(identifier)),
+    ))
+    .parse(input)?;
     let (input, _) = char('{')(input)?;
-    let (input, fields) = many0(field_definition).parse(input)?;
+    let (input, members) = many0(type_member).parse(input)?;
     let (input, _) = ws(char('}')).parse(input)?;
 
-    let mut field_map = IndexMap::new();
-    for (fname, fdef) in fields {
-        field_map.insert(fname, fdef);
-    }
-
     Ok((
         input,
-        TypeDef {
+        RawTypeDef {
             name: name.to_string(),
-            fields: field_map,
+            extends: extends
+                .unwrap_or_default()
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            members,
         },
     ))
 }
 
+fn type_member(input: &str) -> IResult<&str, TypeMember> {
+    alt((
+        map(
+            terminated(
+                preceded(ws(tag("...")), identifier),
+                pair(sp, opt(char(','))),
+            ),
+            |base| TypeMember::Spread(base.to_string()),
+        ),
+        map(field_definition, |(name, def)| TypeMember::Field(name, def)),
+    ))
+    .parse(input)
+}
+
 fn field_definition(input: &str) -> IResult<&str, (String, FieldDef)> {
-    let (input, _) = multispace0(input)?;
+    let (input, _) = sp(input)?;
     let (input, name) = identifier(input)?;
     let (input, optional) = opt(char('?')).parse(input)?;
     let (input, _) = ws(char(':')).parse(input)?;
     let (input, ty) = type_expr(input)?;
-    let (input, _) = multispace0(input)?;
+    let (input, _) = sp(input)?;
+    let (input, _) = opt(char(',')).parse(input)?;
+    let (input, _) = sp(input)?;
 
     Ok((
         input,
@@ -148,119 +479,466 @@ fn field_definition(input: &str) -> IResult<&str, (String, FieldDef)> {
 }
 
 fn type_expr(input: &str) -> IResult<&str, RecType> {
-    alt((
-        map(tag("string"), |_| RecType::String),
-        map(tag("int"), |_| RecType::Int),
-        map(tag("float"), |_| RecType::Float),
-        map(tag("bool"), |_| RecType::Bool),
-        map(tag("url"), |_| RecType::Url),
-        map(tag("socket"), |_| RecType::Socket),
-        map(tag("pubkey"), |_| RecType::Pubkey),
-        array_type,
-        map(identifier, |s| RecType::Object(s.to_string())),
-    ))
+    alt((array_type, tuple_type, named_type)).parse(input)
+}
+
+/// A type name, with arguments for the built-in types that take them
+/// (`datetime(after: "..")`).
+fn named_type(input: &str) -> IResult<&str, RecType> {
+    let (rest, name) = identifier(input)?;
+    if name == "url"
+        && let Ok((rest, schemes)) = url_schemes(rest)
+    {
+        let policy = UrlPolicy {
+            schemes,
+            ..UrlPolicy::default()
+        };
+        return Ok((rest, RecType::Url(policy)));
+    }
+    let (rest, args) = opt(type_args).parse(rest)?;
+    let ty = match args {
+        None => Some(builtin_type(name)),
+        Some(args) => type_with_args(name, args),
+    };
+    match ty {
+        Some(ty) => Ok((rest, ty)),
+        None => Err(nom::Err::Failure(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Verify,
+        ))),
+    }
+}
+
+/// The `<postgres, postgresql>` in `url<postgres, postgresql>`.
+fn url_schemes(input: &str) -> IResult<&str, Vec<String>> {
+    delimited(
+        char('<'),
+        separated_list1(
+            ws(char(',')),
+            ws(map(
+                recognize(pair(
+                    alpha1,
+                    many0(alt((alphanumeric1, tag("+"), tag("-"), tag(".")))),
+                )),
+                str::to_string,
+            )),
+        ),
+        char('>'),
+    )
     .parse(input)
 }
 
+/// An argument to a built-in type: `32` or `name: value`.
+type TypeArg<'a> = (Option<&'a str>, RecValue);
+
+/// `(value, name: value, ..)` arguments to a built-in type.
+fn type_args(input: &str) -> IResult<&str, Vec<TypeArg<'_>>> {
+    delimited(
+        char('('),
+        terminated(
+            separated_list0(
+                ws(char(',')),
+                ws(pair(opt(terminated(identifier, ws(char(':')))), rec_value)),
+            ),
+            opt(ws(char(','))),
+        ),
+        char(')'),
+    )
+    .parse(input)
+}
+
+fn type_with_args(name: &str, args: Vec<TypeArg>) -> Option<RecType> {
+    match name {
+        "bytes" => byte_length(args).map(|len| RecType::Bytes(None, len)),
+        "hex" => byte_length(args).map(|len| RecType::Bytes(Some(ByteEncoding::Hex), len)),
+        "base64" => byte_length(args).map(|len| RecType::Bytes(Some(ByteEncoding::Base64), len)),
+        "decimal" => decimal_limits(args).map(RecType::Decimal),
+        "url" => url_policy(args).map(RecType::Url),
+        "date" => time_bounds(args).map(RecType::Date),
+        "time" => time_bounds(args).map(RecType::Time),
+        "datetime" => time_bounds(args).map(RecType::DateTime),
+        "pubkey" => match args.as_slice() {
+            [(Some("on_curve"), RecValue::Bool(on_curve))] => {
+                Some(RecType::Pubkey(Some(*on_curve)))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The `32` in `bytes(32)`.
+fn byte_length(args: Vec<TypeArg>) -> Option<Option<usize>> {
+    match args.as_slice() {
+        [(None, RecValue::Int(len))] => Some(Some(usize::try_from(*len).ok()?)),
+        _ => None,
+    }
+}
+
+fn decimal_limits(args: Vec<TypeArg>) -> Option<DecimalLimits> {
+    let mut limits = DecimalLimits::default();
+    for (name, value) in args {
+        let RecValue::Int(limit) = value else {
+            return None;
+        };
+        let limit = u32::try_from(limit).ok()?;
+        match name {
+            Some("precision") => limits.precision = Some(limit),
+            Some("scale") => limits.scale = Some(limit),
+            _ => return None,
+        }
+    }
+    Some(limits)
+}
+
+fn url_policy(args: Vec<TypeArg>) -> Option<UrlPolicy> {
+    let mut policy = UrlPolicy::default();
+    for (name, value) in args {
+        match (name, value) {
+            (Some("scheme"), RecValue::String(scheme)) => policy.schemes = vec![scheme],
+            (Some("scheme"), RecValue::Array(schemes)) => {
+                policy.schemes = schemes
+                    .into_iter()
+                    .map(|scheme| match scheme {
+                        RecValue::String(s) => Some(s),
+                        _ => None,
+                    })
+                    .collect::<Option<_>>()?;
+            }
+            (Some("host"), RecValue::Bool(host)) => policy.host = Some(host),
+            (Some("path"), RecValue::Bool(path)) => policy.path = Some(path),
+            _ => return None,
+        }
+    }
+    Some(policy)
+}
+
+fn time_bounds(args: Vec<TypeArg>) -> Option<TimeBounds> {
+    let mut bounds = TimeBounds::default();
+    for (name, value) in args {
+        let bound = match value {
+            RecValue::String(s) | RecValue::Date(s) | RecValue::Time(s) | RecValue::DateTime(s) => {
+                s
+            }
+            _ => return None,
+        };
+        match name {
+            Some("after") => bounds.after = Some(bound),
+            Some("before") => bounds.before = Some(bound),
+            _ => return None,
+        }
+    }
+    Some(bounds)
+}
+
+/// A built-in type keyword, or the name of a user-defined type.
+fn builtin_type(name: &str) -> RecType {
+    match name {
+        "string" => RecType::String,
+        "int" => RecType::Int,
+        "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" => {
+            RecType::SizedInt(IntType::from_name(name).unwrap())
+        }
+        "float" => RecType::Float,
+        "decimal" => RecType::Decimal(DecimalLimits::default()),
+        "bytes" => RecType::Bytes(None, None),
+        "hex" => RecType::Bytes(Some(ByteEncoding::Hex), None),
+        "base64" => RecType::Bytes(Some(ByteEncoding::Base64), None),
+        "bool" => RecType::Bool,
+        "url" => RecType::Url(UrlPolicy::default()),
+        "socket" => RecType::Socket,
+        "socket4" => RecType::Socket4,
+        "socket6" => RecType::Socket6,
+        "hostport" => RecType::HostPort,
+        "ip" => RecType::Ip,
+        "cidr" => RecType::Cidr,
+        "mac" => RecType::Mac,
+        "duration" => RecType::Duration,
+        "size" => RecType::Size,
+        "date" => RecType::Date(TimeBounds::default()),
+        "time" => RecType::Time(TimeBounds::default()),
+        "datetime" => RecType::DateTime(TimeBounds::default()),
+        "pubkey" => RecType::Pubkey(None),
+        "secp256k1" => RecType::Secp256k1,
+        "evm_address" => RecType::EvmAddress,
+        "secret" => RecType::Secret,
+        "any" => RecType::Any,
+        _ => RecType::Object(name.to_string()),
+    }
+}
+
 fn array_type(input: &str) -> IResult<&str, RecType> {
     let (input, _) = char('[')(input)?;
-    let (input, inner) = type_expr(input)?;
+    let (input, inner) = ws(type_expr).parse(input)?;
+    let (input, len) = opt(preceded(ws(char(';')), ws(digit1))).parse(input)?;
     let (input, _) = char(']')(input)?;
-    Ok((input, RecType::Array(Box::new(inner))))
+    match len {
+        Some(len) => match len.parse() {
+            Ok(len) => Ok((input, RecType::FixedArray(Box::new(inner), len))),
+            Err(_) => Err(nom::Err::Failure(nom::error::Error::new(
+                len,
+                nom::error::ErrorKind::Digit,
+            ))),
+        },
+        None => Ok((input, RecType::Array(Box::new(inner)))),
+    }
+}
+
+fn tuple_type(input: &str) -> IResult<&str, RecType> {
+    let (input, _) = char('(')(input)?;
+    let (input, types) = separated_list0(ws(char(',')), ws(type_expr)).parse(input)?;
+    let (input, _) = opt(ws(char(','))).parse(input)?;
+    let (input, _) = char(')')(input)?;
+    Ok((input, RecType::Tuple(types)))
 }
 
 fn object(input: &str) -> IResult<&str, RecObject> {
     let (input, _) = ws(char('{')).parse(input)?;
-    let (input, pairs) = separated_list0(ws(char(',')), key_value_pair).parse(input)?;
-    let (input, _) = opt(char(',')).parse(input)?;
+    let (input, fields) = object_body(input)?;
     let (input, _) = ws(char('}')).parse(input)?;
+    Ok((input, RecObject::new(fields)))
+}
+
+/// Key/value pairs separated by newlines or commas; a trailing comma is
+/// allowed.
+fn object_body(input: &str) -> IResult<&str, IndexMap<String, RecValue>> {
+    let (input, pairs) = many0(terminated(key_value_pair, opt(ws(char(','))))).parse(input)?;
 
     let mut fields = IndexMap::new();
     for (k, v) in pairs {
         fields.insert(k, v);
     }
-
-    Ok((input, RecObject { fields }))
+    Ok((input, fields))
 }
 
 fn key_value_pair(input: &str) -> IResult<&str, (String, RecValue)> {
-    let (input, key) = ws(identifier).parse(input)?;
+    let (input, key) = ws(alt((map(identifier, str::to_string), string_literal))).parse(input)?;
     let (input, _) = ws(char(':')).parse(input)?;
     let (input, value) = ws(rec_value).parse(input)?;
-    Ok((input, (key.to_string(), value)))
+    Ok((input, (key, value)))
 }
 
 fn rec_value(input: &str) -> IResult<&str, RecValue> {
     alt((
-        map(string_literal, RecValue::String),
+        map(string_literal, string_value),
+        quantity,
+        decimal_literal,
         map(float, RecValue::Float),
-        map(integer, RecValue::Int),
+        integer_value,
         map(boolean, RecValue::Bool),
-        map(tag("null"), |_| RecValue::Null),
-        url_value,
-        socket_value,
-        pubkey_value,
+        map(keyword("null"), |_| RecValue::Null),
+        pda,
+        secret_ref,
+        env_value,
+        string_constructor,
         enum_variant,
         map(array, RecValue::Array),
+        map(tuple, RecValue::Tuple),
         typed_object,
         map(object, RecValue::Object),
+        map(identifier, |variant| RecValue::EnumVariant {
+            enum_name: String::new(),
+            variant: variant.to_string(),
+            data: EnumVariantData::Unit,
+        }),
     ))
     .parse(input)
 }
 
 fn typed_object(input: &str) -> IResult<&str, RecValue> {
-    let (input, _type_name) = identifier(input)?;
-    let (input, _) = multispace0(input)?;
-    let (input, obj) = object(input)?;
+    let (input, type_name) = identifier(input)?;
+    let (input, _) = sp(input)?;
+    let (input, mut obj) = object(input)?;
+    obj.type_name = Some(type_name.to_string());
     Ok((input, RecValue::Object(obj)))
 }
 
-fn url_value(input: &str) -> IResult<&str, RecValue> {
-    let (input, _) = tag("url(")(input)?;
-    let (input, url) = string_literal(input)?;
+/// A duration (`30s`, `1h30m`) or size (`512MiB`) literal.
+fn quantity(input: &str) -> IResult<&str, RecValue> {
+    let (rest, text) = recognize((digit1, alpha1, alphanumeric0)).parse(input)?;
+    if let Some(duration) = units::parse_duration(text) {
+        Ok((rest, RecValue::Duration(duration)))
+    } else if let Some(bytes) = units::parse_size(text) {
+        Ok((rest, RecValue::Size(bytes)))
+    } else {
+        Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Verify,
+        )))
+    }
+}
+
+/// A decimal literal with a `d` suffix, such as `1.25d`. The point is
+/// required, since `5d` is five days.
+fn decimal_literal(input: &str) -> IResult<&str, RecValue> {
+    let (rest, text) = terminated(
+        recognize((opt(char('-')), digit1, char('.'), digit1)),
+        keyword("d"),
+    )
+    .parse(input)?;
+    match text.parse() {
+        Ok(decimal) => Ok((rest, RecValue::Decimal(decimal))),
+        Err(_) => Err(too_large(input)),
+    }
+}
+
+/// `url("..")`, `socket("..")` and the other constructors that wrap a
+/// single string.
+fn string_constructor(input: &str) -> IResult<&str, RecValue> {
+    let (rest, (name, arg)) = constructor_call(input)?;
+    let value = match name {
+        "url" => RecValue::Url(arg),
+        "socket" => RecValue::Socket(arg),
+        "hostport" => RecValue::HostPort(arg),
+        "ip" => RecValue::Ip(arg),
+        "cidr" => RecValue::Cidr(arg),
+        "mac" => RecValue::Mac(arg),
+        "date" => RecValue::Date(arg),
+        "time" => RecValue::Time(arg),
+        "datetime" => RecValue::DateTime(arg),
+        "pubkey" => RecValue::Pubkey(arg),
+        "keypair_pubkey" => RecValue::KeypairPubkey(arg),
+        "secp256k1" => RecValue::Secp256k1(arg),
+        "evm_address" => RecValue::EvmAddress(arg),
+        "secret" => RecValue::Secret(arg.into()),
+        "encrypted" => RecValue::Encrypted(arg),
+        "hex" => RecValue::Hex(arg),
+        "base64" => RecValue::Base64(arg),
+        "decimal" => match arg.parse() {
+            Ok(decimal) => RecValue::Decimal(decimal),
+            Err(_) => {
+                return Err(nom::Err::Failure(nom::error::Error::new(
+                    input,
+                    nom::error::ErrorKind::Float,
+                )));
+            }
+        },
+        _ => {
+            return Err(nom::Err::Error(nom::error::Error::new(
+                input,
+                nom::error::ErrorKind::Tag,
+            )));
+        }
+    };
+    Ok((rest, value))
+}
+
+/// A name applied to one string, as in `url( "https://example.com" )`.
+fn constructor_call(input: &str) -> IResult<&str, (&str, String)> {
+    pair(
+        identifier,
+        delimited(pair(char('('), sp), string_literal, pair(sp, char(')'))),
+    )
+    .parse(input)
+}
+
+/// `pda(program_id, [seeds..])`, where the program id is a `pubkey(..)` or
+/// a plain string.
+fn pda(input: &str) -> IResult<&str, RecValue> {
+    let (input, _) = terminated(keyword("pda"), char('(')).parse(input)?;
+    let (input, program_id) = ws(alt((
+        delimited(
+            terminated(keyword("pubkey"), char('(')),
+            string_literal,
+            char(')'),
+        ),
+        string_literal,
+    )))
+    .parse(input)?;
+    let (input, _) = char(',')(input)?;
+    let (input, seeds) = ws(delimited(
+        char('['),
+        many0(terminated(ws(pda_seed), opt(ws(char(','))))),
+        char(']'),
+    ))
+    .parse(input)?;
     let (input, _) = char(')')(input)?;
-    Ok((input, RecValue::Url(url)))
+    Ok((input, RecValue::Pda(Pda { program_id, seeds })))
 }
 
-fn socket_value(input: &str) -> IResult<&str, RecValue> {
-    let (input, _) = tag("socket(")(input)?;
-    let (input, addr) = string_literal(input)?;
+/// A string value: a [`RecValue::Template`] if it refers to environment
+/// variables, otherwise the text with `$${` read as `${`.
+fn string_value(text: String) -> RecValue {
+    let parts = env::split_template(&text);
+    match parts.as_slice() {
+        [env::Part::Text(literal)] => RecValue::String(literal.clone()),
+        _ => RecValue::Template(text),
+    }
+}
+
+/// `env("NAME")` or `env("NAME", default)`.
+fn env_value(input: &str) -> IResult<&str, RecValue> {
+    let (input, _) = terminated(keyword("env"), char('(')).parse(input)?;
+    let (input, name) = ws(string_literal).parse(input)?;
+    let (input, default) = opt(preceded(char(','), ws(rec_value))).parse(input)?;
     let (input, _) = char(')')(input)?;
-    Ok((input, RecValue::Socket(addr)))
+    Ok((
+        input,
+        RecValue::Env {
+            name,
+            default: default.map(Box::new),
+        },
+    ))
 }
 
-fn pubkey_value(input: &str) -> IResult<&str, RecValue> {
-    let (input, _) = tag("pubkey(")(input)?;
-    let (input, key) = string_literal(input)?;
+/// `secret_ref("provider", "name")`.
+fn secret_ref(input: &str) -> IResult<&str, RecValue> {
+    let (input, _) = terminated(keyword("secret_ref"), char('(')).parse(input)?;
+    let (input, provider) = ws(string_literal).parse(input)?;
+    let (input, _) = char(',')(input)?;
+    let (input, name) = ws(string_literal).parse(input)?;
     let (input, _) = char(')')(input)?;
-    Ok((input, RecValue::Pubkey(key)))
+    Ok((input, RecValue::SecretRef { provider, name }))
 }
 
+/// A string, `pubkey(..)`, `hex(..)`, `base64(..)` or sized integer such as
+/// `u8(1)`.
+fn pda_seed(input: &str) -> IResult<&str, PdaSeed> {
+    if let Ok((rest, s)) = string_literal(input) {
+        return Ok((rest, PdaSeed::String(s)));
+    }
+    let (rest, name) = terminated(identifier, char('(')).parse(input)?;
+    let fail = || nom::Err::Failure(nom::error::Error::new(input, nom::error::ErrorKind::Verify));
+    let (rest, seed) = match name {
+        "pubkey" => map(string_literal, PdaSeed::Pubkey).parse(rest)?,
+        "hex" => map(string_literal, PdaSeed::Hex).parse(rest)?,
+        "base64" => map(string_literal, PdaSeed::Base64).parse(rest)?,
+        _ => {
+            let ty = IntType::from_name(name)
+                .filter(|ty| *ty != IntType::U128)
+                .ok_or_else(fail)?;
+            let (rest, text) = ws(recognize(pair(opt(char('-')), digit1))).parse(rest)?;
+            let value: i128 = text.parse().map_err(|_| fail())?;
+            if value < ty.min() || u128::try_from(value).is_ok_and(|v| v > ty.max()) {
+                return Err(fail());
+            }
+            (rest, PdaSeed::Int(ty, value))
+        }
+    };
+    let (rest, _) = char(')')(rest)?;
+    Ok((rest, seed))
+}
+
+/// `Enum.Variant`, or the `.Variant` shorthand whose enum is inferred from
+/// the field's type.
 fn enum_variant(input: &str) -> IResult<&str, RecValue> {
-    let (input, enum_name) = identifier(input)?;
+    let (input, enum_name) = opt(identifier).parse(input)?;
+    let enum_name = enum_name.unwrap_or_default();
     let (input, _) = char('.')(input)?;
     let (input, variant) = identifier(input)?;
 
-    if let Ok((input2, _)) = multispace0::<&str, nom::error::Error<&str>>(input) {
-        if let Ok((input2, _)) = char::<&str, nom::error::Error<&str>>('{')(input2) {
-            let (input2, pairs) = separated_list0(ws(char(',')), key_value_pair).parse(input2)?;
-            let (input2, _) = opt(char(',')).parse(input2)?;
-            let (input2, _) = ws(char('}')).parse(input2)?;
-
-            let mut fields = IndexMap::new();
-            for (k, v) in pairs {
-                fields.insert(k, v);
-            }
+    if let Ok((input2, _)) = preceded(sp, char::<&str, nom::error::Error<&str>>('{')).parse(input) {
+        let (input2, fields) = object_body(input2)?;
+        let (input2, _) = ws(char('}')).parse(input2)?;
 
-            return Ok((
-                input2,
-                RecValue::EnumVariant {
-                    enum_name: enum_name.to_string(),
-                    variant: variant.to_string(),
-                    data: EnumVariantData::Struct(fields),
-                },
-            ));
-        }
+        return Ok((
+            input2,
+            RecValue::EnumVariant {
+                enum_name: enum_name.to_string(),
+                variant: variant.to_string(),
+                data: EnumVariantData::Struct(fields),
+            },
+        ));
     }
 
     if let Ok((input2, _)) = char::<&str, nom::error::Error<&str>>('(')(input) {
@@ -289,24 +967,122 @@ fn enum_variant(input: &str) -> IResult<&str, RecValue> {
 
 fn array(input: &str) -> IResult<&str, Vec<RecValue>> {
     let (input, _) = char('[')(input)?;
-    let (input, values) = separated_list0(ws(char(',')), ws(rec_value)).parse(input)?;
-    let (input, _) = opt(char(',')).parse(input)?;
+    let (input, values) = many0(terminated(ws(rec_value), opt(ws(char(','))))).parse(input)?;
     let (input, _) = ws(char(']')).parse(input)?;
     Ok((input, values))
 }
 
+fn tuple(input: &str) -> IResult<&str, Vec<RecValue>> {
+    let (input, _) = char('(')(input)?;
+    let (input, values) = separated_list0(ws(char(',')), ws(rec_value)).parse(input)?;
+    let (input, _) = opt(ws(char(','))).parse(input)?;
+    let (input, _) = char(')')(input)?;
+    Ok((input, values))
+}
+
+/// A double-quoted string. `\"`, `\\`, `\n`, `\r`, `\t` and `\u{..}` are
+/// escapes; any other backslash is kept as written.
 fn string_literal(input: &str) -> IResult<&str, String> {
-    let (input, _) = char('"')(input)?;
-    let (input, content) = take_while(|c| c != '"')(input)?;
-    let (input, _) = char('"')(input)?;
-    Ok((input, content.to_string()))
+    let (mut input, _) = char('"')(input)?;
+    let mut content = String::new();
+    loop {
+        let (rest, chunk) = take_while(|c| c != '"' && c != '\\')(input)?;
+        content.push_str(chunk);
+        if let Ok((rest, _)) = char::<&str, nom::error::Error<&str>>('"')(rest) {
+            return Ok((rest, content));
+        }
+        let (rest, _) = char('\\')(rest)?;
+        let (rest, unescaped) = alt((
+            value('"', char('"')),
+            value('\\', char('\\')),
+            value('\n', char('n')),
+            value('\r', char('r')),
+            value('\t', char('t')),
+            unicode_escape,
+            value('\\', peek(nom::character::complete::anychar)),
+        ))
+        .parse(rest)?;
+        content.push(unescaped);
+        input = rest;
+    }
+}
+
+/// Describes a failed parse. Most errors are reported as nom left them;
+/// an invalid `decimal("..")` literal names the accepted format.
+fn parse_error(e: nom::Err<nom::error::Error<&str>>) -> RecError {
+    if let nom::Err::Failure(failure) = &e
+        && failure.code == nom::error::ErrorKind::Float
+        && let Ok((_, ("decimal", literal))) = constructor_call(failure.input)
+        && let Err(invalid) = literal.parse::<crate::Decimal>()
+    {
+        return invalid;
+    }
+    RecError::ParseError(format!("{:?}", e))
+}
+
+/// Parses a single value, such as the text of a syntax tree value node.
+/// Shorthand enum variants are left unresolved.
+pub(crate) fn parse_value(input: &str) -> Result<RecValue, RecError> {
+    match ws(rec_value).parse(input) {
+        Ok(("", value)) => Ok(value),
+        Ok((rest, _)) => Err(RecError::ParseError(format!(
+            "Unexpected trailing input: {}",
+            rest.lines().next().unwrap_or_default()
+        ))),
+        Err(e) => Err(parse_error(e)),
+    }
+}
+
+/// The contents of a quoted string token, with escapes resolved.
+pub(crate) fn unquote(text: &str) -> Option<String> {
+    match string_literal(text) {
+        Ok(("", s)) => Some(s),
+        _ => None,
+    }
+}
+
+fn unicode_escape(input: &str) -> IResult<&str, char> {
+    let (rest, hex) = delimited(
+        tag("u{"),
+        take_while(|c: char| c.is_ascii_hexdigit()),
+        char('}'),
+    )
+    .parse(input)?;
+    match u32::from_str_radix(hex, 16).ok().and_then(char::from_u32) {
+        Some(c) => Ok((rest, c)),
+        None => Err(nom::Err::Failure(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Char,
+        ))),
+    }
 }
 
 fn integer(input: &str) -> IResult<&str, i64> {
-    let (input, sign) = opt(char('-')).parse(input)?;
-    let (input, digits) = digit1(input)?;
-    let value = digits.parse::<i64>().unwrap();
-    Ok((input, if sign.is_some() { -value } else { value }))
+    let (rest, text) = recognize(pair(opt(char('-')), digit1)).parse(input)?;
+    match text.parse() {
+        Ok(value) => Ok((rest, value)),
+        Err(_) => Err(too_large(input)),
+    }
+}
+
+/// An integer literal: an `Int` when it fits in an `i64`, and a `UInt` up to
+/// `u128::MAX` above that.
+fn integer_value(input: &str) -> IResult<&str, RecValue> {
+    let (rest, text) = recognize(pair(opt(char('-')), digit1)).parse(input)?;
+    if let Ok(value) = text.parse() {
+        Ok((rest, RecValue::Int(value)))
+    } else if let Ok(value) = text.parse() {
+        Ok((rest, RecValue::UInt(value)))
+    } else {
+        Err(too_large(input))
+    }
+}
+
+fn too_large(input: &str) -> nom::Err<nom::error::Error<&str>> {
+    nom::Err::Failure(nom::error::Error::new(
+        input,
+        nom::error::ErrorKind::TooLarge,
+    ))
 }
 
 fn float(input: &str) -> IResult<&str, f64> {
@@ -319,7 +1095,7 @@ fn float(input: &str) -> IResult<&str, f64> {
 }
 
 fn boolean(input: &str) -> IResult<&str, bool> {
-    alt((value(true, tag("true")), value(false, tag("false")))).parse(input)
+    alt((value(true, keyword("true")), value(false, keyword("false")))).parse(input)
 }
 
 fn identifier(input: &str) -> IResult<&str, &str> {
@@ -330,9 +1106,29 @@ fn identifier(input: &str) -> IResult<&str, &str> {
     .parse(input)
 }
 
+/// Matches `word` only when it is not the prefix of a longer identifier.
+fn keyword<'a>(
+    word: &'static str,
+) -> impl Parser<&'a str, Output = &'a str, Error = nom::error::Error<&'a str>> {
+    terminated(tag(word), not(peek(alt((alphanumeric1, tag("_"))))))
+}
+
+/// Whitespace, `// line` comments and `/* block */` comments.
+fn sp(input: &str) -> IResult<&str, ()> {
+    value(
+        (),
+        many0(alt((
+            multispace1,
+            recognize(pair(tag("//"), opt(is_not("\r\n")))),
+            recognize((tag("/*"), take_until("*/"), tag("*/"))),
+        ))),
+    )
+    .parse(input)
+}
+
 fn ws<'a, F, O>(inner: F) -> impl Parser<&'a str, Output = O, Error = nom::error::Error<&'a str>>
 where
     F: Parser<&'a str, Output = O, Error = nom::error::Error<&'a str>>,
 {
-    delimited(multispace0, inner, multispace0)
+    delimited(sp, inner, sp)
 }
@@ -0,0 +1,732 @@
+//! Writes REC documents and values back out as `.rec` source text.
+//!
+//! [`Printer::print_document`] prints a parsed [`RecDocument`];
+//! [`Printer::format`] reformats a [`syntax`] tree and keeps its comments.
+//! Both lay text out the same way, so printing a document and formatting
+//! its source agree everywhere except for comments.
+
+use crate::env;
+use crate::syntax::{self, Element, Node, NodeKind, TokenKind};
+use crate::units;
+use crate::{
+    EnumDef, EnumVariant, EnumVariantData, FieldDef, Pda, PdaSeed, RecDocument, RecError,
+    RecObject, RecValue, TypeDef,
+};
+use indexmap::IndexMap;
+use std::fmt::Write;
+
+/// Where commas go in blocks and lists that are printed one item per line.
+/// Items on a single line are always separated by `, `.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Commas {
+    /// Between the items of arrays and enum bodies; none in objects and type
+    /// bodies.
+    #[default]
+    Lists,
+    /// After every item, including the last.
+    Trailing,
+    /// Never; newlines separate items.
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyOrder {
+    /// Keep keys in the order they were written.
+    #[default]
+    Preserve,
+    /// Sort object keys. Type and enum bodies keep their order, since it is
+    /// significant.
+    Sorted,
+}
+
+#[derive(Debug, Clone)]
+pub struct Printer {
+    pub indent: String,
+    pub commas: Commas,
+    pub key_order: KeyOrder,
+    /// Arrays that would run past this column are broken into one item per
+    /// line.
+    pub max_width: usize,
+}
+
+impl Default for Printer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Printer {
+    pub fn new() -> Self {
+        Self {
+            indent: "    ".to_string(),
+            commas: Commas::default(),
+            key_order: KeyOrder::default(),
+            max_width: 80,
+        }
+    }
+
+    /// Prints includes, enums, types and the root object of `doc`.
+    pub fn print_document(&self, doc: &RecDocument) -> String {
+        let mut items: Vec<Item> = doc
+            .includes
+            .iter()
+            .map(|path| {
+                let mut text = "#include ".to_string();
+                write_string(&mut text, path);
+                Item::text(text)
+            })
+            .collect();
+        items.extend(
+            doc.type_aliases
+                .iter()
+                .map(|(name, ty)| Item::text(format!("@alias {} = {}", name, ty))),
+        );
+        items.extend(doc.enum_definitions.values().map(enum_item));
+        items.extend(
+            doc.type_definitions
+                .values()
+                .map(|def| type_item(def, &doc.type_definitions)),
+        );
+        items.push(Item::new(object_parts(&doc.root)));
+        self.render_document(items, Vec::new())
+    }
+
+    /// Prints `root` as the top-level object of a document.
+    pub fn print_root(&self, root: &RecObject) -> String {
+        self.render_document(vec![Item::new(object_parts(root))], Vec::new())
+    }
+
+    pub fn print_value(&self, value: &RecValue) -> String {
+        let mut out = String::new();
+        self.write_parts(&mut out, &value_parts(value), 0);
+        out
+    }
+
+    /// Reformats a document parsed with [`syntax::parse`], keeping its
+    /// comments and the blank lines between items.
+    pub fn format(&self, tree: &Node) -> String {
+        let (items, dangling) = items_from_syntax(&tree.children);
+        self.render_document(items, dangling)
+    }
+
+    /// Parses and reformats `.rec` source.
+    pub fn format_str(&self, source: &str) -> Result<String, RecError> {
+        Ok(self.format(&syntax::parse(source)?))
+    }
+
+    fn render_document(&self, items: Vec<Item>, dangling: Vec<String>) -> String {
+        let mut out = String::new();
+        let mut previous_multiline = false;
+        for (i, item) in items.iter().enumerate() {
+            let multiline = item.parts.iter().any(|p| matches!(p, Part::Group(_)));
+            if i > 0 && (item.blank_before || multiline || previous_multiline) {
+                out.push('\n');
+            }
+            self.write_item(&mut out, item, 0, false);
+            previous_multiline = multiline;
+        }
+        if !dangling.is_empty() && !items.is_empty() {
+            out.push('\n');
+        }
+        for comment in &dangling {
+            out.push_str(comment);
+            out.push('\n');
+        }
+        out
+    }
+
+    fn write_item(&self, out: &mut String, item: &Item, depth: usize, comma: bool) {
+        for comment in &item.leading {
+            self.write_indent(out, depth);
+            out.push_str(comment);
+            out.push('\n');
+        }
+        self.write_indent(out, depth);
+        self.write_parts(out, &item.parts, depth);
+        if comma {
+            out.push(',');
+        }
+        if let Some(comment) = &item.trailing {
+            out.push_str("  ");
+            out.push_str(comment);
+        }
+        out.push('\n');
+    }
+
+    fn write_parts(&self, out: &mut String, parts: &[Part], depth: usize) {
+        for part in parts {
+            match part {
+                Part::Text(text) => out.push_str(text),
+                Part::Group(group) => self.write_group(out, group, depth),
+            }
+        }
+    }
+
+    fn write_group(&self, out: &mut String, group: &Group, depth: usize) {
+        let (open, close) = group.kind.delimiters();
+        out.push_str(open);
+        if group.items.is_empty() && group.dangling.is_empty() {
+            out.push_str(close);
+            return;
+        }
+        if group.fits_inline() {
+            let mut inline = String::new();
+            write_inline(&mut inline, group);
+            let column = out.len() - out.rfind('\n').map_or(0, |i| i + 1);
+            // Tuples also hold call arguments, as in `url("..")`, which must
+            // stay on one line.
+            if group.kind == GroupKind::Tuple || column + inline.len() < self.max_width {
+                out.push_str(&inline);
+                return;
+            }
+        }
+
+        let mut items: Vec<&Item> = group.items.iter().collect();
+        let sorted = group.kind == GroupKind::Object && self.key_order == KeyOrder::Sorted;
+        if sorted {
+            items.sort_by(|a, b| a.key.cmp(&b.key));
+        }
+
+        out.push('\n');
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 && item.blank_before && !sorted {
+                out.push('\n');
+            }
+            let last = i + 1 == items.len();
+            let comma = match (group.kind, self.commas) {
+                (GroupKind::Tuple, _) => !last,
+                (_, Commas::Trailing) => true,
+                (GroupKind::Array | GroupKind::Variants, Commas::Lists) => !last,
+                _ => false,
+            };
+            self.write_item(out, item, depth + 1, comma);
+        }
+        for comment in &group.dangling {
+            self.write_indent(out, depth + 1);
+            out.push_str(comment);
+            out.push('\n');
+        }
+        self.write_indent(out, depth);
+        out.push_str(close);
+    }
+
+    fn write_indent(&self, out: &mut String, depth: usize) {
+        for _ in 0..depth {
+            out.push_str(&self.indent);
+        }
+    }
+}
+
+/// Writes `value` on a single line, with `, ` between object entries.
+pub(crate) fn print_inline(value: &RecValue) -> String {
+    let mut out = String::new();
+    write_parts_inline(&mut out, &value_parts(value));
+    out
+}
+
+/// Writes a group on one line, without its opening delimiter.
+fn write_inline(out: &mut String, group: &Group) {
+    for (i, item) in group.items.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        write_parts_inline(out, &item.parts);
+    }
+    out.push_str(group.kind.delimiters().1);
+}
+
+fn write_parts_inline(out: &mut String, parts: &[Part]) {
+    for part in parts {
+        match part {
+            Part::Text(text) => out.push_str(text),
+            Part::Group(inner) => {
+                out.push_str(inner.kind.delimiters().0);
+                write_inline(out, inner);
+            }
+        }
+    }
+}
+
+/// A line of output, or the first line of a multi-line construct, with the
+/// comments attached to it.
+struct Item {
+    /// The key of an object entry, used for sorting.
+    key: Option<String>,
+    /// Comments on their own lines before the item.
+    leading: Vec<String>,
+    blank_before: bool,
+    parts: Vec<Part>,
+    /// A comment after the item on the same line.
+    trailing: Option<String>,
+}
+
+impl Item {
+    fn new(parts: Vec<Part>) -> Self {
+        Self {
+            key: None,
+            leading: Vec::new(),
+            blank_before: false,
+            parts,
+            trailing: None,
+        }
+    }
+
+    fn text(text: String) -> Self {
+        Self::new(vec![Part::Text(text)])
+    }
+
+    fn has_comments(&self) -> bool {
+        !self.leading.is_empty()
+            || self.trailing.is_some()
+            || self.parts.iter().any(|part| match part {
+                Part::Group(group) => group.has_comments(),
+                Part::Text(_) => false,
+            })
+    }
+}
+
+enum Part {
+    Text(String),
+    Group(Group),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GroupKind {
+    Object,
+    /// The fields of a type or struct variant.
+    Fields,
+    Variants,
+    Array,
+    Tuple,
+}
+
+impl GroupKind {
+    fn delimiters(self) -> (&'static str, &'static str) {
+        match self {
+            GroupKind::Object | GroupKind::Fields | GroupKind::Variants => ("{", "}"),
+            GroupKind::Array => ("[", "]"),
+            GroupKind::Tuple => ("(", ")"),
+        }
+    }
+}
+
+struct Group {
+    kind: GroupKind,
+    items: Vec<Item>,
+    /// Comments after the last item.
+    dangling: Vec<String>,
+}
+
+impl Group {
+    fn new(kind: GroupKind, items: Vec<Item>) -> Self {
+        Self {
+            kind,
+            items,
+            dangling: Vec::new(),
+        }
+    }
+
+    fn has_comments(&self) -> bool {
+        !self.dangling.is_empty() || self.items.iter().any(Item::has_comments)
+    }
+
+    /// Arrays and tuples stay on one line unless they hold objects or
+    /// comments; arrays also break when they hold other arrays.
+    fn fits_inline(&self) -> bool {
+        matches!(self.kind, GroupKind::Array | GroupKind::Tuple)
+            && !self.has_comments()
+            && self.items.iter().all(|item| {
+                item.parts.iter().all(|part| match part {
+                    Part::Text(_) => true,
+                    Part::Group(inner) if inner.items.is_empty() => true,
+                    Part::Group(inner) => {
+                        inner.fits_inline()
+                            && (self.kind == GroupKind::Tuple || inner.kind == GroupKind::Tuple)
+                    }
+                })
+            })
+    }
+}
+
+fn enum_item(def: &EnumDef) -> Item {
+    let variants = def
+        .variants
+        .iter()
+        .map(|variant| match variant {
+            EnumVariant::Unit(name, None) => Item::text(name.clone()),
+            EnumVariant::Unit(name, Some(value)) => Item::text(format!("{} = {}", name, value)),
+            EnumVariant::Tuple(name, types) => Item::new(vec![
+                Part::Text(name.clone()),
+                Part::Group(Group::new(
+                    GroupKind::Tuple,
+                    types.iter().map(|ty| Item::text(ty.to_string())).collect(),
+                )),
+            ]),
+            EnumVariant::Struct(name, fields) => Item::new(vec![
+                Part::Text(format!("{} ", name)),
+                Part::Group(Group::new(GroupKind::Fields, field_items(fields))),
+            ]),
+        })
+        .collect();
+    Item::new(vec![
+        Part::Text(format!("@enum {} ", def.name)),
+        Part::Group(Group::new(GroupKind::Variants, variants)),
+    ])
+}
+
+/// Prints a type as `extends` its bases plus the fields it declares or
+/// redeclares, which are those that differ from what the bases give it.
+fn type_item(def: &TypeDef, types: &IndexMap<String, TypeDef>) -> Item {
+    let mut header = format!("@type {}", def.name);
+    if !def.bases.is_empty() {
+        write!(header, " extends {}", def.bases.join(", ")).unwrap();
+    }
+    header.push(' ');
+
+    let mut inherited = IndexMap::new();
+    for base in def.bases.iter().filter_map(|base| types.get(base)) {
+        inherited.extend(base.fields.iter());
+    }
+    let own: IndexMap<String, FieldDef> = def
+        .fields
+        .iter()
+        .filter(|(name, field)| inherited.get(name) != Some(field))
+        .map(|(name, field)| (name.clone(), field.clone()))
+        .collect();
+    Item::new(vec![
+        Part::Text(header),
+        Part::Group(Group::new(GroupKind::Fields, field_items(&own))),
+    ])
+}
+
+fn field_items(fields: &IndexMap<String, FieldDef>) -> Vec<Item> {
+    fields
+        .iter()
+        .map(|(name, field)| field_item(name, field))
+        .collect()
+}
+
+fn field_item(name: &str, field: &FieldDef) -> Item {
+    let optional = if field.optional { "?" } else { "" };
+    Item::text(format!("{}{}: {}", name, optional, field.ty))
+}
+
+fn object_parts(obj: &RecObject) -> Vec<Part> {
+    let mut parts = Vec::new();
+    if let Some(type_name) = &obj.type_name {
+        parts.push(Part::Text(format!("{} ", type_name)));
+    }
+    parts.push(Part::Group(Group::new(
+        GroupKind::Object,
+        entry_items(&obj.fields),
+    )));
+    parts
+}
+
+fn entry_items(fields: &IndexMap<String, RecValue>) -> Vec<Item> {
+    fields
+        .iter()
+        .map(|(key, value)| {
+            let mut text = String::new();
+            write_key(&mut text, key);
+            text.push_str(": ");
+            let mut parts = vec![Part::Text(text)];
+            parts.extend(value_parts(value));
+            Item {
+                key: Some(key.clone()),
+                ..Item::new(parts)
+            }
+        })
+        .collect()
+}
+
+fn value_parts(value: &RecValue) -> Vec<Part> {
+    let mut text = String::new();
+    match value {
+        RecValue::String(s) => write_string(&mut text, &env::escape_template(s)),
+        RecValue::Template(s) => write_string(&mut text, s),
+        RecValue::Env { name, default } => {
+            write_call(&mut text, "env", name);
+            if let Some(default) = default {
+                text.pop();
+                write!(text, ", {})", print_inline(default)).unwrap();
+            }
+        }
+        RecValue::Int(i) => write!(text, "{}", i).unwrap(),
+        RecValue::UInt(u) => write!(text, "{}", u).unwrap(),
+        RecValue::Hex(s) => write_call(&mut text, "hex", s),
+        RecValue::Base64(s) => write_call(&mut text, "base64", s),
+        RecValue::Decimal(d) => write_call(&mut text, "decimal", &d.to_string()),
+        RecValue::Float(f) => write_float(&mut text, *f),
+        RecValue::Bool(b) => write!(text, "{}", b).unwrap(),
+        RecValue::Null => text.push_str("null"),
+        RecValue::Url(u) => write_call(&mut text, "url", u),
+        RecValue::Socket(s) => write_call(&mut text, "socket", s),
+        RecValue::HostPort(s) => write_call(&mut text, "hostport", s),
+        RecValue::Ip(s) => write_call(&mut text, "ip", s),
+        RecValue::Cidr(s) => write_call(&mut text, "cidr", s),
+        RecValue::Mac(s) => write_call(&mut text, "mac", s),
+        RecValue::Date(s) => write_call(&mut text, "date", s),
+        RecValue::Time(s) => write_call(&mut text, "time", s),
+        RecValue::DateTime(s) => write_call(&mut text, "datetime", s),
+        RecValue::Duration(d) => text.push_str(&units::format_duration(*d)),
+        RecValue::Size(bytes) => text.push_str(&units::format_size(*bytes)),
+        RecValue::Pubkey(p) => write_call(&mut text, "pubkey", p),
+        RecValue::Pda(pda) => write_pda(&mut text, pda),
+        RecValue::KeypairPubkey(file) => write_call(&mut text, "keypair_pubkey", file),
+        RecValue::Secp256k1(k) => write_call(&mut text, "secp256k1", k),
+        RecValue::EvmAddress(a) => write_call(&mut text, "evm_address", a),
+        RecValue::Secret(s) => write_call(&mut text, "secret", s.expose_secret()),
+        RecValue::Encrypted(payload) => write_call(&mut text, "encrypted", payload),
+        RecValue::SecretRef { provider, name } => {
+            text.push_str("secret_ref(");
+            write_string(&mut text, provider);
+            text.push_str(", ");
+            write_string(&mut text, name);
+            text.push(')');
+        }
+        RecValue::Array(values) => return vec![Part::Group(list(GroupKind::Array, values))],
+        RecValue::Tuple(values) => return vec![Part::Group(list(GroupKind::Tuple, values))],
+        RecValue::Object(obj) => return object_parts(obj),
+        RecValue::EnumVariant {
+            enum_name,
+            variant,
+            data,
+        } => {
+            write!(text, "{}.{}", enum_name, variant).unwrap();
+            match data {
+                EnumVariantData::Unit => {}
+                EnumVariantData::Tuple(values) => {
+                    return vec![
+                        Part::Text(text),
+                        Part::Group(list(GroupKind::Tuple, values)),
+                    ];
+                }
+                EnumVariantData::Struct(fields) => {
+                    text.push(' ');
+                    return vec![
+                        Part::Text(text),
+                        Part::Group(Group::new(GroupKind::Object, entry_items(fields))),
+                    ];
+                }
+            }
+        }
+    }
+    vec![Part::Text(text)]
+}
+
+fn list(kind: GroupKind, values: &[RecValue]) -> Group {
+    Group::new(
+        kind,
+        values.iter().map(|v| Item::new(value_parts(v))).collect(),
+    )
+}
+
+/// Turns the children of a document or bracketed group into items, tying
+/// each comment to the item it sits on the line of or above. Comments after
+/// the last item are returned separately.
+fn items_from_syntax(children: &[Element]) -> (Vec<Item>, Vec<String>) {
+    let mut items: Vec<Item> = Vec::new();
+    let mut pending = Vec::new();
+    let mut newlines = 0;
+    let mut blank = false;
+    for child in children {
+        match child {
+            Element::Token(token) => match token.kind {
+                TokenKind::Newline => {
+                    newlines += 1;
+                    blank |= newlines > 1;
+                }
+                TokenKind::LineComment | TokenKind::BlockComment => {
+                    let comment = token.text.trim_end().to_string();
+                    match items.last_mut() {
+                        Some(last) if newlines == 0 && last.trailing.is_none() => {
+                            last.trailing = Some(comment)
+                        }
+                        _ => pending.push(comment),
+                    }
+                    newlines = 0;
+                }
+                _ => {}
+            },
+            Element::Node(node) => {
+                let mut item = item_from_syntax(node);
+                pending.append(&mut item.leading);
+                item.leading = std::mem::take(&mut pending);
+                item.blank_before = blank;
+                items.push(item);
+                newlines = 0;
+                blank = false;
+            }
+        }
+    }
+    (items, pending)
+}
+
+fn group_from_syntax(kind: GroupKind, node: &Node) -> Group {
+    let inner = &node.children[1..node.children.len() - 1];
+    let (items, dangling) = items_from_syntax(inner);
+    Group {
+        kind,
+        items,
+        dangling,
+    }
+}
+
+/// Converts one item node. Comments inside the item, outside any bracketed
+/// group, are moved into `leading`.
+fn item_from_syntax(node: &Node) -> Item {
+    if let Some(kind) = group_kind(NodeKind::Document, node.kind) {
+        return Item::new(vec![Part::Group(group_from_syntax(kind, node))]);
+    }
+    let mut item = Item::new(Vec::new());
+    let mut text = String::new();
+    let mut prev: Option<&str> = None;
+    collect_parts(node, &mut item, &mut text, &mut prev);
+    if !text.is_empty() {
+        item.parts.push(Part::Text(text));
+    }
+    if node.kind == NodeKind::Entry {
+        item.key = node.key();
+    }
+    item
+}
+
+fn collect_parts<'a>(
+    node: &'a Node,
+    item: &mut Item,
+    text: &mut String,
+    prev: &mut Option<&'a str>,
+) {
+    let mut space = false;
+    for child in &node.children {
+        match child {
+            Element::Token(token) if token.kind.is_comment() => {
+                item.leading.push(token.text.trim_end().to_string());
+            }
+            Element::Token(token) if token.kind.is_trivia() => space = true,
+            Element::Token(token) => {
+                if needs_space(*prev, &token.text, space) {
+                    text.push(' ');
+                }
+                text.push_str(&token.text);
+                *prev = Some(&token.text);
+                space = false;
+            }
+            Element::Node(child) => match group_kind(node.kind, child.kind) {
+                Some(kind) => {
+                    let space_before = match kind {
+                        GroupKind::Tuple => needs_space(*prev, "(", space),
+                        _ => !text.is_empty(),
+                    };
+                    if space_before && !text.ends_with(' ') {
+                        text.push(' ');
+                    }
+                    if !text.is_empty() {
+                        item.parts.push(Part::Text(std::mem::take(text)));
+                    }
+                    item.parts.push(Part::Group(group_from_syntax(kind, child)));
+                    *prev = None;
+                    space = false;
+                }
+                None => collect_parts(child, item, text, prev),
+            },
+        }
+    }
+}
+
+/// Which layout a bracketed node gets, given the node it appears in.
+fn group_kind(parent: NodeKind, kind: NodeKind) -> Option<GroupKind> {
+    match kind {
+        NodeKind::Object => Some(GroupKind::Object),
+        NodeKind::Array => Some(GroupKind::Array),
+        NodeKind::Tuple => Some(GroupKind::Tuple),
+        NodeKind::Body if parent == NodeKind::Enum => Some(GroupKind::Variants),
+        NodeKind::Body => Some(GroupKind::Fields),
+        _ => None,
+    }
+}
+
+/// Spacing between the tokens of one item: a single space after `,`, `:`,
+/// `;` and around `=`, none inside brackets, and otherwise one space where
+/// the source had any whitespace.
+fn needs_space(prev: Option<&str>, next: &str, had_space: bool) -> bool {
+    let Some(prev) = prev else {
+        return false;
+    };
+    if matches!(next, "," | ";" | ":" | ")" | "]" | ">" | "?") {
+        return false;
+    }
+    if matches!(prev, "," | ";" | ":" | "=") {
+        return true;
+    }
+    if matches!(prev, "(" | "[" | "<" | "." | "...") || next == "." || next == "(" {
+        return false;
+    }
+    had_space || next == "="
+}
+
+pub(crate) fn write_key(out: &mut String, key: &str) {
+    let mut chars = key.chars();
+    let is_identifier = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if is_identifier {
+        out.push_str(key);
+    } else {
+        write_string(out, key);
+    }
+}
+
+fn write_call(out: &mut String, name: &str, arg: &str) {
+    out.push_str(name);
+    out.push('(');
+    write_string(out, arg);
+    out.push(')');
+}
+
+fn write_pda(out: &mut String, pda: &Pda) {
+    out.push_str("pda(");
+    write_call(out, "pubkey", &pda.program_id);
+    out.push_str(", [");
+    for (i, seed) in pda.seeds.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        match seed {
+            PdaSeed::String(s) => write_string(out, s),
+            PdaSeed::Pubkey(p) => write_call(out, "pubkey", p),
+            PdaSeed::Hex(s) => write_call(out, "hex", s),
+            PdaSeed::Base64(s) => write_call(out, "base64", s),
+            PdaSeed::Int(ty, value) => write!(out, "{}({})", ty.name(), value).unwrap(),
+        }
+    }
+    out.push_str("])");
+}
+
+/// Writes a quoted string literal, escaping what the parser unescapes.
+pub(crate) fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => write!(out, "\\u{{{:x}}}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Floats always keep a decimal point so they read back as floats.
+fn write_float(out: &mut String, f: f64) {
+    let s = f.to_string();
+    out.push_str(&s);
+    if !s.contains('.') {
+        out.push_str(".0");
+    }
+}
@@ -0,0 +1,400 @@
+//! The key and address types behind `pubkey(..)`, `pda(..)`,
+//! `secp256k1(..)` and `evm_address(..)` values.
+
+use crate::{IntType, RecError};
+use base58::{FromBase58, ToBase58};
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::Sha256;
+use sha3::{Digest, Keccak256};
+use std::fmt;
+use std::str::FromStr;
+
+/// A 32-byte public key, written in base58.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pubkey([u8; 32]);
+
+impl Pubkey {
+    pub const fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    pub fn to_bytes(self) -> [u8; 32] {
+        self.0
+    }
+
+    /// Whether the bytes are a point on the ed25519 curve. Keys with a
+    /// secret half always are; Solana program-derived addresses never are.
+    pub fn is_on_curve(&self) -> bool {
+        CompressedEdwardsY(self.0).decompress().is_some()
+    }
+
+    /// The address a Solana program would derive from exactly these seeds:
+    /// the SHA-256 hash of the seeds, the program id and a fixed marker,
+    /// provided it falls off the curve.
+    pub fn create_program_address(
+        seeds: &[&[u8]],
+        program_id: &Pubkey,
+    ) -> Result<Pubkey, RecError> {
+        if seeds.len() > MAX_SEEDS {
+            return Err(RecError::InvalidPubkey(format!(
+                "{} seeds given; program addresses take at most {}",
+                seeds.len(),
+                MAX_SEEDS
+            )));
+        }
+        let mut hasher = Sha256::new();
+        for (i, seed) in seeds.iter().enumerate() {
+            if seed.len() > MAX_SEED_LEN {
+                return Err(RecError::InvalidPubkey(format!(
+                    "seed {} is {} bytes long; seeds are at most {}",
+                    i,
+                    seed.len(),
+                    MAX_SEED_LEN
+                )));
+            }
+            hasher.update(seed);
+        }
+        hasher.update(program_id.0);
+        hasher.update(PDA_MARKER);
+        let address = Pubkey(hasher.finalize().into());
+        if address.is_on_curve() {
+            return Err(RecError::InvalidPubkey(format!(
+                "{} derives an address on the curve",
+                describe_seeds(seeds)
+            )));
+        }
+        Ok(address)
+    }
+
+    /// The program address for `seeds` plus the highest bump seed, counting
+    /// down from 255, that lands off the curve.
+    pub fn find_program_address(
+        seeds: &[&[u8]],
+        program_id: &Pubkey,
+    ) -> Result<(Pubkey, u8), RecError> {
+        if seeds.len() >= MAX_SEEDS {
+            return Err(RecError::InvalidPubkey(format!(
+                "{} seeds given; at most {} fit beside the bump",
+                seeds.len(),
+                MAX_SEEDS - 1
+            )));
+        }
+        for bump in (1..=u8::MAX).rev() {
+            let bump_seed = [bump];
+            let mut with_bump = seeds.to_vec();
+            with_bump.push(&bump_seed);
+            match Self::create_program_address(&with_bump, program_id) {
+                Ok(address) => return Ok((address, bump)),
+                Err(e) if with_bump.iter().any(|s| s.len() > MAX_SEED_LEN) => return Err(e),
+                Err(_) => {}
+            }
+        }
+        Err(RecError::InvalidPubkey(format!(
+            "no bump seed puts {} off the curve",
+            describe_seeds(seeds)
+        )))
+    }
+}
+
+const MAX_SEEDS: usize = 16;
+const MAX_SEED_LEN: usize = 32;
+const PDA_MARKER: &[u8] = b"ProgramDerivedAddress";
+
+fn describe_seeds(seeds: &[&[u8]]) -> String {
+    let seeds: Vec<_> = seeds.iter().map(hex::encode).collect();
+    format!("seeds [{}]", seeds.join(", "))
+}
+
+/// A Solana program-derived address, written
+/// `pda(program_id, ["vault", pubkey(..), u8(1)])`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pda {
+    /// The program id in base58.
+    pub program_id: String,
+    pub seeds: Vec<PdaSeed>,
+}
+
+/// One seed of a [`Pda`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PdaSeed {
+    /// A string, as its UTF-8 bytes.
+    String(String),
+    /// A `pubkey(..)`, as its 32 bytes.
+    Pubkey(String),
+    /// A `hex(..)` byte string.
+    Hex(String),
+    /// A `base64(..)` byte string.
+    Base64(String),
+    /// An integer such as `u8(1)` or `u64(7)`, as little-endian bytes of its
+    /// width.
+    Int(IntType, i128),
+}
+
+impl Pda {
+    /// The derived address and the bump seed it took.
+    pub fn derive(&self) -> Result<(Pubkey, u8), RecError> {
+        let program_id: Pubkey = self.program_id.parse()?;
+        let seeds = self
+            .seeds
+            .iter()
+            .map(PdaSeed::to_bytes)
+            .collect::<Result<Vec<_>, _>>()?;
+        let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+        Pubkey::find_program_address(&seeds, &program_id)
+    }
+}
+
+impl PdaSeed {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, RecError> {
+        match self {
+            PdaSeed::String(s) => Ok(s.as_bytes().to_vec()),
+            PdaSeed::Pubkey(p) => Ok(p.parse::<Pubkey>()?.0.to_vec()),
+            PdaSeed::Hex(s) => crate::value::decode_hex(s),
+            PdaSeed::Base64(s) => crate::value::decode_base64(s),
+            PdaSeed::Int(ty, value) => {
+                let bytes = value.to_le_bytes();
+                Ok(bytes[..ty.bits() as usize / 8].to_vec())
+            }
+        }
+    }
+}
+
+impl FromStr for Pubkey {
+    type Err = RecError;
+
+    fn from_str(s: &str) -> Result<Self, RecError> {
+        let bytes = s
+            .from_base58()
+            .map_err(|_| RecError::InvalidPubkey(format!("Invalid Base58 encoding: {}", s)))?;
+        let bytes: [u8; 32] = bytes.try_into().map_err(|bytes: Vec<u8>| {
+            RecError::InvalidPubkey(format!(
+                "Invalid pubkey length: expected 32 bytes, got {}",
+                bytes.len()
+            ))
+        })?;
+        Ok(Self(bytes))
+    }
+}
+
+impl From<[u8; 32]> for Pubkey {
+    fn from(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl fmt::Display for Pubkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.to_base58())
+    }
+}
+
+impl fmt::Debug for Pubkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Pubkey({})", self)
+    }
+}
+
+/// A secp256k1 public key, written in hex as a 33-byte compressed or
+/// 65-byte uncompressed SEC1 point, optionally prefixed with `0x`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Secp256k1Pubkey([u8; 33]);
+
+impl Secp256k1Pubkey {
+    /// Checks that `bytes` is a compressed or uncompressed point on the
+    /// curve.
+    pub fn from_sec1_bytes(bytes: &[u8]) -> Result<Self, RecError> {
+        match bytes {
+            [2 | 3, ..] if bytes.len() == 33 => {}
+            [4, ..] if bytes.len() == 65 => {}
+            [] => {
+                return Err(RecError::InvalidSecp256k1Key(
+                    "expected 33 bytes compressed or 65 uncompressed, got 0".to_string(),
+                ));
+            }
+            [tag @ 2..=4, ..] => {
+                let (form, len) = if *tag == 4 {
+                    ("uncompressed", 65)
+                } else {
+                    ("compressed", 33)
+                };
+                return Err(RecError::InvalidSecp256k1Key(format!(
+                    "a {:02x} prefix is for {}-byte {} keys, got {} bytes",
+                    tag,
+                    len,
+                    form,
+                    bytes.len()
+                )));
+            }
+            [tag, ..] => {
+                return Err(RecError::InvalidSecp256k1Key(format!(
+                    "the first byte must be 02 or 03 (compressed) or 04 (uncompressed), not {:02x}",
+                    tag
+                )));
+            }
+        }
+        let key = k256::PublicKey::from_sec1_bytes(bytes).map_err(|_| {
+            RecError::InvalidSecp256k1Key(format!(
+                "{} is not a point on the curve",
+                hex::encode(bytes)
+            ))
+        })?;
+        let mut compressed = [0; 33];
+        compressed.copy_from_slice(key.to_encoded_point(true).as_bytes());
+        Ok(Self(compressed))
+    }
+
+    /// The compressed form: a `02` or `03` prefix and the x coordinate.
+    pub fn as_bytes(&self) -> &[u8; 33] {
+        &self.0
+    }
+
+    /// The uncompressed form: a `04` prefix and both coordinates.
+    pub fn to_uncompressed(&self) -> [u8; 65] {
+        let key = k256::PublicKey::from_sec1_bytes(&self.0).expect("checked on construction");
+        let mut uncompressed = [0; 65];
+        uncompressed.copy_from_slice(key.to_encoded_point(false).as_bytes());
+        uncompressed
+    }
+
+    /// The Ethereum address of this key: the last 20 bytes of the Keccak-256
+    /// hash of both coordinates.
+    pub fn evm_address(&self) -> EvmAddress {
+        let hash = Keccak256::digest(&self.to_uncompressed()[1..]);
+        let mut address = [0; 20];
+        address.copy_from_slice(&hash[12..]);
+        EvmAddress(address)
+    }
+}
+
+impl FromStr for Secp256k1Pubkey {
+    type Err = RecError;
+
+    fn from_str(s: &str) -> Result<Self, RecError> {
+        let bytes = hex::decode(s.strip_prefix("0x").unwrap_or(s))
+            .map_err(|_| RecError::InvalidSecp256k1Key(format!("'{}' is not valid hex", s)))?;
+        Self::from_sec1_bytes(&bytes)
+    }
+}
+
+impl fmt::Display for Secp256k1Pubkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl fmt::Debug for Secp256k1Pubkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Secp256k1Pubkey({})", self)
+    }
+}
+
+/// A 20-byte Ethereum address, displayed with its EIP-55 checksum.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EvmAddress([u8; 20]);
+
+impl EvmAddress {
+    pub const fn new(bytes: [u8; 20]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+
+    pub fn to_bytes(self) -> [u8; 20] {
+        self.0
+    }
+
+    /// The `0x`-prefixed address with each hex letter upper-cased where the
+    /// Keccak-256 hash of the lower-case address has its high bit set.
+    pub fn to_checksummed(&self) -> String {
+        let lower = hex::encode(self.0);
+        let hash = Keccak256::digest(lower.as_bytes());
+        let mut text = String::with_capacity(42);
+        text.push_str("0x");
+        for (i, c) in lower.chars().enumerate() {
+            let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0xf;
+            text.push(if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            });
+        }
+        text
+    }
+}
+
+impl FromStr for EvmAddress {
+    type Err = RecError;
+
+    /// Mixed-case addresses must carry a valid EIP-55 checksum. All-lower
+    /// and all-upper case addresses carry none and are accepted as they are.
+    fn from_str(s: &str) -> Result<Self, RecError> {
+        let digits = s
+            .strip_prefix("0x")
+            .ok_or_else(|| RecError::InvalidEvmAddress(format!("'{}' must start with 0x", s)))?;
+        let bytes = hex::decode(digits)
+            .map_err(|_| RecError::InvalidEvmAddress(format!("'{}' is not valid hex", s)))?;
+        let bytes: [u8; 20] = bytes.try_into().map_err(|bytes: Vec<u8>| {
+            RecError::InvalidEvmAddress(format!(
+                "'{}' is {} bytes long; addresses are 20",
+                s,
+                bytes.len()
+            ))
+        })?;
+        let address = Self(bytes);
+        let mixed_case = digits.chars().any(|c| c.is_ascii_lowercase())
+            && digits.chars().any(|c| c.is_ascii_uppercase());
+        if mixed_case && address.to_checksummed() != s {
+            return Err(RecError::InvalidEvmAddress(format!(
+                "'{}' has a bad EIP-55 checksum; expected {}",
+                s,
+                address.to_checksummed()
+            )));
+        }
+        Ok(address)
+    }
+}
+
+impl From<[u8; 20]> for EvmAddress {
+    fn from(bytes: [u8; 20]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl fmt::Display for EvmAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_checksummed())
+    }
+}
+
+impl fmt::Debug for EvmAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EvmAddress({})", self)
+    }
+}
+
+macro_rules! impl_serde_as_str {
+    ($($ty:ty),*) => {$(
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+                s.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    )*};
+}
+
+impl_serde_as_str!(Pubkey, Secp256k1Pubkey, EvmAddress);
@@ -0,0 +1,220 @@
+//! The string behind `secret("..")` values, and the providers that
+//! `secret_ref("provider", "name")` values are read from.
+
+use crate::RecError;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use zeroize::Zeroizing;
+
+/// What `Debug`, `Display` and serialization write in place of a secret.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// The newtype struct name `Secret` serializes and deserializes under, so
+/// `rec::to_value` and `rec::from_value` can tell it from a plain string.
+/// Other serializers see [`REDACTED`].
+pub(crate) const SECRET_TOKEN: &str = "$rec::Secret";
+
+/// A string that is never printed or serialized as written. Read it with
+/// [`expose_secret`](Secret::expose_secret). The memory is zeroed when the
+/// secret is dropped.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(Zeroizing<String>);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(Zeroizing::new(value.into()))
+    }
+
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+/// Writes [`REDACTED`] to every format but REC: `rec::to_value` and
+/// `rec::to_string_pretty` give a `secret(..)` value, so a secret survives
+/// a round trip. `rec to-json --reveal` and
+/// `SerializeOptions::reveal_secrets` write `secret(..)` values as they
+/// were written instead.
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(SECRET_TOKEN, &Payload(self))
+    }
+}
+
+/// The string inside a serialized [`Secret`].
+struct Payload<'a>(&'a Secret);
+
+impl Serialize for Payload<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // `rec::to_value` hands the payload to its own `SecretSerializer`,
+        // which keeps the value as a `RecValue::Secret`.
+        if std::any::type_name::<S>() == std::any::type_name::<crate::ser::SecretSerializer>() {
+            serializer.serialize_str(self.0.expose_secret())
+        } else {
+            serializer.serialize_str(REDACTED)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SecretVisitor;
+
+        impl<'de> Visitor<'de> for SecretVisitor {
+            type Value = Secret;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a string")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Secret, E> {
+                Ok(Secret::new(s))
+            }
+
+            fn visit_string<E: de::Error>(self, s: String) -> Result<Secret, E> {
+                Ok(Secret::new(s))
+            }
+
+            fn visit_newtype_struct<D: Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> Result<Secret, D::Error> {
+                deserializer.deserialize_str(self)
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(SECRET_TOKEN, SecretVisitor)
+    }
+}
+
+/// A source of secrets for `secret_ref("provider", "name")` values.
+/// Register providers with [`Loader::with_secret_provider`](crate::Loader::with_secret_provider).
+pub trait SecretProvider: Send + Sync {
+    /// The secret called `name`, or `None` if the provider does not have
+    /// one. Errors are for a provider that could not be read at all.
+    fn get(&self, name: &str) -> Result<Option<Secret>, RecError>;
+}
+
+/// Reads secrets from environment variables. Registered as `"env"` on
+/// every [`Loader`](crate::Loader).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnvProvider;
+
+impl SecretProvider for EnvProvider {
+    fn get(&self, name: &str) -> Result<Option<Secret>, RecError> {
+        match std::env::var(name) {
+            Ok(value) => Ok(Some(Secret::new(value))),
+            Err(std::env::VarError::NotPresent) => Ok(None),
+            Err(std::env::VarError::NotUnicode(_)) => Err(RecError::MissingSecret(format!(
+                "environment variable '{}' is not valid UTF-8",
+                name
+            ))),
+        }
+    }
+}
+
+/// Reads each secret from a file of the same name in `dir`, as Docker
+/// (`/run/secrets`) and Kubernetes volume mounts lay them out. One trailing
+/// newline is dropped.
+#[derive(Debug, Clone)]
+pub struct DirProvider {
+    pub dir: PathBuf,
+}
+
+impl DirProvider {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl SecretProvider for DirProvider {
+    fn get(&self, name: &str) -> Result<Option<Secret>, RecError> {
+        if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+            return Err(RecError::MissingSecret(format!(
+                "'{}' is not a file name in {}",
+                name,
+                self.dir.display()
+            )));
+        }
+        let path = self.dir.join(name);
+        let mut text = match std::fs::read_to_string(&path) {
+            Ok(text) => Zeroizing::new(text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(RecError::MissingSecret(format!(
+                    "cannot read {}: {}",
+                    path.display(),
+                    e
+                )));
+            }
+        };
+        if text.ends_with('\n') {
+            text.pop();
+            if text.ends_with('\r') {
+                text.pop();
+            }
+        }
+        Ok(Some(Secret(text)))
+    }
+}
+
+/// Secrets held in memory, for tests.
+#[derive(Clone, Default)]
+pub struct MemoryProvider {
+    secrets: HashMap<String, Secret>,
+}
+
+impl MemoryProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.insert(name, value);
+        self
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.secrets.insert(name.into(), Secret::new(value));
+    }
+}
+
+impl fmt::Debug for MemoryProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.secrets.keys()).finish()
+    }
+}
+
+impl SecretProvider for MemoryProvider {
+    fn get(&self, name: &str) -> Result<Option<Secret>, RecError> {
+        Ok(self.secrets.get(name).cloned())
+    }
+}
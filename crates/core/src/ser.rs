@@ -0,0 +1,760 @@
+//! A serde `Serializer` that turns Rust values into [`RecValue`]s.
+
+use crate::decimal::DECIMAL_TOKEN;
+use crate::printer::Printer;
+use crate::secret::SECRET_TOKEN;
+use crate::{EnumVariantData, RecError, RecObject, RecValue};
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde::ser::{self, Impossible};
+use std::time::Duration;
+
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<RecValue, RecError> {
+    value.serialize(Serializer)
+}
+
+/// Serializes `value` as the root object of a `.rec` document.
+///
+/// Rust enums are written as `Enum.Variant`, `Enum.Variant(..)` and
+/// `Enum.Variant { .. }`, named after the Rust enum type. No `@enum`
+/// definitions are written, so the output parses but only validates once
+/// those are added.
+pub fn to_string_pretty<T: Serialize + ?Sized>(value: &T) -> Result<String, RecError> {
+    match to_value(value)? {
+        RecValue::Object(root) => Ok(Printer::new().print_root(&root)),
+        other => Err(ser::Error::custom(format!(
+            "the root of a REC document must be an object, got {}",
+            other.type_name()
+        ))),
+    }
+}
+
+pub struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = RecValue;
+    type Error = RecError;
+    type SerializeSeq = SerializeVec;
+    type SerializeTuple = SerializeVec;
+    type SerializeTupleStruct = SerializeVec;
+    type SerializeTupleVariant = SerializeVec;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeMap;
+
+    fn serialize_bool(self, v: bool) -> Result<RecValue, RecError> {
+        Ok(RecValue::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<RecValue, RecError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<RecValue, RecError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<RecValue, RecError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<RecValue, RecError> {
+        Ok(RecValue::Int(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<RecValue, RecError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<RecValue, RecError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<RecValue, RecError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<RecValue, RecError> {
+        Ok(v.into())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<RecValue, RecError> {
+        match u128::try_from(v) {
+            Ok(v) => Ok(v.into()),
+            Err(_) => i64::try_from(v)
+                .map(RecValue::Int)
+                .map_err(|_| ser::Error::custom(format!("{} is below the smallest int", v))),
+        }
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<RecValue, RecError> {
+        Ok(v.into())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<RecValue, RecError> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<RecValue, RecError> {
+        if !v.is_finite() {
+            return Err(ser::Error::custom(format!("REC has no literal for {}", v)));
+        }
+        Ok(RecValue::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<RecValue, RecError> {
+        Ok(RecValue::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<RecValue, RecError> {
+        Ok(RecValue::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<RecValue, RecError> {
+        Ok(RecValue::Array(
+            v.iter().map(|b| RecValue::Int((*b).into())).collect(),
+        ))
+    }
+
+    fn serialize_none(self) -> Result<RecValue, RecError> {
+        Ok(RecValue::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<RecValue, RecError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<RecValue, RecError> {
+        Ok(RecValue::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<RecValue, RecError> {
+        Ok(RecValue::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<RecValue, RecError> {
+        Ok(enum_variant(name, variant, EnumVariantData::Unit))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<RecValue, RecError> {
+        if name == SECRET_TOKEN {
+            return value.serialize(SecretSerializer);
+        }
+        match value.serialize(self)? {
+            RecValue::String(s) if name == DECIMAL_TOKEN => Ok(RecValue::Decimal(s.parse()?)),
+            value => Ok(value),
+        }
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<RecValue, RecError> {
+        let value = value.serialize(Serializer)?;
+        Ok(enum_variant(
+            name,
+            variant,
+            EnumVariantData::Tuple(vec![value]),
+        ))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeVec, RecError> {
+        Ok(SerializeVec::new(len, Sequence::Array))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeVec, RecError> {
+        Ok(SerializeVec::new(Some(len), Sequence::Tuple))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeVec, RecError> {
+        Ok(SerializeVec::new(Some(len), Sequence::Tuple))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVec, RecError> {
+        Ok(SerializeVec::new(
+            Some(len),
+            Sequence::Variant(name, variant),
+        ))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap, RecError> {
+        Ok(SerializeMap::new(len, None))
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<SerializeMap, RecError> {
+        let mut map = SerializeMap::new(Some(len), None);
+        map.duration = name == "Duration";
+        Ok(map)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeMap, RecError> {
+        Ok(SerializeMap::new(Some(len), Some((name, variant))))
+    }
+}
+
+fn enum_variant(name: &str, variant: &str, data: EnumVariantData) -> RecValue {
+    RecValue::EnumVariant {
+        enum_name: name.to_string(),
+        variant: variant.to_string(),
+        data,
+    }
+}
+
+/// What a [`SerializeVec`] produces once all elements are in.
+enum Sequence {
+    Array,
+    Tuple,
+    Variant(&'static str, &'static str),
+}
+
+pub struct SerializeVec {
+    values: Vec<RecValue>,
+    kind: Sequence,
+}
+
+impl SerializeVec {
+    fn new(len: Option<usize>, kind: Sequence) -> Self {
+        Self {
+            values: Vec::with_capacity(len.unwrap_or(0)),
+            kind,
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RecError> {
+        self.values.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> RecValue {
+        match self.kind {
+            Sequence::Array => RecValue::Array(self.values),
+            Sequence::Tuple => RecValue::Tuple(self.values),
+            Sequence::Variant(name, variant) => {
+                enum_variant(name, variant, EnumVariantData::Tuple(self.values))
+            }
+        }
+    }
+}
+
+impl ser::SerializeSeq for SerializeVec {
+    type Ok = RecValue;
+    type Error = RecError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RecError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RecValue, RecError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for SerializeVec {
+    type Ok = RecValue;
+    type Error = RecError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RecError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RecValue, RecError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeVec {
+    type Ok = RecValue;
+    type Error = RecError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RecError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RecValue, RecError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeVec {
+    type Ok = RecValue;
+    type Error = RecError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RecError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RecValue, RecError> {
+        Ok(self.finish())
+    }
+}
+
+pub struct SerializeMap {
+    fields: IndexMap<String, RecValue>,
+    next_key: Option<String>,
+    variant: Option<(&'static str, &'static str)>,
+    /// Set for serde's `Duration` struct, which becomes a duration value.
+    duration: bool,
+}
+
+impl SerializeMap {
+    fn new(len: Option<usize>, variant: Option<(&'static str, &'static str)>) -> Self {
+        Self {
+            fields: IndexMap::with_capacity(len.unwrap_or(0)),
+            next_key: None,
+            variant,
+            duration: false,
+        }
+    }
+
+    fn insert<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> Result<(), RecError> {
+        let value = value.serialize(Serializer)?;
+        if self.fields.insert(key.clone(), value).is_some() {
+            return Err(RecError::DuplicateKey(key));
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> RecValue {
+        if self.duration
+            && let Some(duration) = as_duration(&self.fields)
+        {
+            return RecValue::Duration(duration);
+        }
+        match self.variant {
+            Some((name, variant)) => {
+                enum_variant(name, variant, EnumVariantData::Struct(self.fields))
+            }
+            None => RecValue::Object(RecObject::new(self.fields)),
+        }
+    }
+}
+
+/// The `{ secs, nanos }` fields serde writes for a `std::time::Duration`.
+fn as_duration(fields: &IndexMap<String, RecValue>) -> Option<Duration> {
+    match (fields.len(), fields.get("secs")?, fields.get("nanos")?) {
+        (2, RecValue::Int(secs), RecValue::Int(nanos)) => Some(Duration::new(
+            (*secs).try_into().ok()?,
+            (*nanos).try_into().ok()?,
+        )),
+        _ => None,
+    }
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = RecValue;
+    type Error = RecError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), RecError> {
+        self.next_key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RecError> {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| ser::Error::custom("value serialized before its key"))?;
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<RecValue, RecError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = RecValue;
+    type Error = RecError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), RecError> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<RecValue, RecError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeMap {
+    type Ok = RecValue;
+    type Error = RecError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), RecError> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<RecValue, RecError> {
+        Ok(self.finish())
+    }
+}
+
+/// Map keys must be strings, or values that display as one (integers,
+/// chars, unit variants).
+struct KeySerializer;
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = RecError;
+    type SerializeSeq = Impossible<String, RecError>;
+    type SerializeTuple = Impossible<String, RecError>;
+    type SerializeTupleStruct = Impossible<String, RecError>;
+    type SerializeTupleVariant = Impossible<String, RecError>;
+    type SerializeMap = Impossible<String, RecError>;
+    type SerializeStruct = Impossible<String, RecError>;
+    type SerializeStructVariant = Impossible<String, RecError>;
+
+    fn serialize_str(self, v: &str) -> Result<String, RecError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_char(self, v: char) -> Result<String, RecError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<String, RecError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<String, RecError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<String, RecError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<String, RecError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<String, RecError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<String, RecError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<String, RecError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<String, RecError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<String, RecError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String, RecError> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, RecError> {
+        value.serialize(self)
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String, RecError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<String, RecError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, RecError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_none(self) -> Result<String, RecError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<String, RecError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit(self) -> Result<String, RecError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, RecError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, RecError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, RecError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, RecError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, RecError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, RecError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, RecError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, RecError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, RecError> {
+        Err(key_must_be_a_string())
+    }
+}
+
+fn key_must_be_a_string() -> RecError {
+    ser::Error::custom("map keys must be strings")
+}
+
+/// Reads the string inside a serialized [`Secret`](crate::Secret). It is
+/// the one serializer a secret writes its value to; every other serializer
+/// sees it redacted.
+pub(crate) struct SecretSerializer;
+
+impl ser::Serializer for SecretSerializer {
+    type Ok = RecValue;
+    type Error = RecError;
+    type SerializeSeq = Impossible<RecValue, RecError>;
+    type SerializeTuple = Impossible<RecValue, RecError>;
+    type SerializeTupleStruct = Impossible<RecValue, RecError>;
+    type SerializeTupleVariant = Impossible<RecValue, RecError>;
+    type SerializeMap = Impossible<RecValue, RecError>;
+    type SerializeStruct = Impossible<RecValue, RecError>;
+    type SerializeStructVariant = Impossible<RecValue, RecError>;
+
+    fn serialize_str(self, v: &str) -> Result<RecValue, RecError> {
+        Ok(RecValue::Secret(v.into()))
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_char(self, _v: char) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_none(self) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_unit(self) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _value: &T,
+    ) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<RecValue, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, RecError> {
+        Err(secret_must_be_a_string())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, RecError> {
+        Err(secret_must_be_a_string())
+    }
+}
+
+fn secret_must_be_a_string() -> RecError {
+    ser::Error::custom("secrets must be strings")
+}
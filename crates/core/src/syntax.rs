@@ -0,0 +1,616 @@
+//! A lossless concrete syntax tree for `.rec` source.
+//!
+//! Unlike [`parse_rec`](crate::parse_rec), which builds a [`RecDocument`]
+//! (crate::RecDocument), this keeps every token of the input, including
+//! whitespace and comments, so that printing a tree gives back the exact
+//! text it was parsed from. The formatter and tooling that rewrites files
+//! in place work on this tree.
+
+use crate::RecError;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Spaces and tabs.
+    Whitespace,
+    /// `\n` or `\r\n`.
+    Newline,
+    LineComment,
+    BlockComment,
+    Ident,
+    /// A literal starting with a digit or `-`, such as `42`, `-1.5` or
+    /// `30s`.
+    Number,
+    /// A double-quoted string, quotes and escapes included.
+    String,
+    /// `@type`, `@enum`, `#include` and other `@`/`#` keywords.
+    Directive,
+    /// Punctuation: one of `{ } [ ] ( ) : , . ? ; = < >`, or `...`.
+    Punct,
+}
+
+impl TokenKind {
+    pub fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            TokenKind::Whitespace
+                | TokenKind::Newline
+                | TokenKind::LineComment
+                | TokenKind::BlockComment
+        )
+    }
+
+    pub fn is_comment(self) -> bool {
+        matches!(self, TokenKind::LineComment | TokenKind::BlockComment)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+}
+
+impl Token {
+    pub fn new(kind: TokenKind, text: impl Into<String>) -> Self {
+        Self {
+            kind,
+            text: text.into(),
+        }
+    }
+
+    pub fn is_punct(&self, punct: &str) -> bool {
+        self.kind == TokenKind::Punct && self.text == punct
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Document,
+    /// `#include "path"`
+    Include,
+    /// `@enum Name { .. }`
+    Enum,
+    /// A variant inside an `@enum` body.
+    Variant,
+    /// `@type Name extends A, B { .. }`
+    Type,
+    /// `name?: type` inside a `@type` or struct variant body.
+    Field,
+    /// `...Base` inside a `@type` body.
+    Spread,
+    /// A type expression, kept as its tokens.
+    TypeExpr,
+    /// Any other `@` directive, up to the end of its line.
+    Directive,
+    /// `{ .. }` holding the members of a type, enum or struct variant.
+    Body,
+    /// `{ .. }` holding key/value entries.
+    Object,
+    /// `[ .. ]`
+    Array,
+    /// `( .. )`: a tuple, or the arguments of `url(..)`, `Enum.V(..)` and
+    /// similar.
+    Tuple,
+    /// `key: value`
+    Entry,
+    /// A value: a literal, a name such as `Enum.Variant` or `url`, optionally
+    /// followed by a tuple or object, or a bare array, tuple or object.
+    Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Element {
+    Token(Token),
+    Node(Node),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub children: Vec<Element>,
+}
+
+impl Node {
+    pub fn new(kind: NodeKind) -> Self {
+        Self {
+            kind,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn tokens(&self) -> impl Iterator<Item = &Token> {
+        self.children.iter().filter_map(|child| match child {
+            Element::Token(token) => Some(token),
+            Element::Node(_) => None,
+        })
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &Node> {
+        self.children.iter().filter_map(|child| match child {
+            Element::Node(node) => Some(node),
+            Element::Token(_) => None,
+        })
+    }
+
+    /// The first child node of the given kind.
+    pub fn node(&self, kind: NodeKind) -> Option<&Node> {
+        self.nodes().find(|node| node.kind == kind)
+    }
+
+    pub fn node_mut(&mut self, kind: NodeKind) -> Option<&mut Node> {
+        self.children.iter_mut().find_map(|child| match child {
+            Element::Node(node) if node.kind == kind => Some(node),
+            _ => None,
+        })
+    }
+
+    /// The first non-trivia token directly under this node.
+    pub fn first_token(&self) -> Option<&Token> {
+        self.tokens().find(|token| !token.kind.is_trivia())
+    }
+
+    /// The key of an [`NodeKind::Entry`], unquoted if it was written as a
+    /// string.
+    pub fn key(&self) -> Option<String> {
+        if self.kind != NodeKind::Entry {
+            return None;
+        }
+        let token = self.first_token()?;
+        match token.kind {
+            TokenKind::Ident => Some(token.text.clone()),
+            TokenKind::String => crate::parser::unquote(&token.text),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for child in &self.children {
+            match child {
+                Element::Token(token) => f.write_str(&token.text)?,
+                Element::Node(node) => write!(f, "{}", node)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses `input` into a syntax tree that prints back to exactly `input`.
+pub fn parse(input: &str) -> Result<Node, RecError> {
+    let tokens = lex(input)?;
+    let mut parser = SyntaxParser { tokens, pos: 0 };
+    parser.document()
+}
+
+/// Parses a single value, as it would appear after `key:`. Whitespace and
+/// comments around it are dropped.
+pub fn parse_value(input: &str) -> Result<Node, RecError> {
+    let tokens = lex(input)?;
+    let mut parser = SyntaxParser { tokens, pos: 0 };
+    parser.skip_trivia_into(&mut Node::new(NodeKind::Value));
+    let value = parser.value()?;
+    parser.skip_trivia_into(&mut Node::new(NodeKind::Value));
+    match parser.peek() {
+        None => Ok(value),
+        Some(token) => Err(parser.error(&format!("unexpected '{}' after value", token.text))),
+    }
+}
+
+pub fn lex(input: &str) -> Result<Vec<Token>, RecError> {
+    let mut tokens = Vec::new();
+    let mut rest = input;
+    while let Some(c) = rest.chars().next() {
+        let (kind, len) = match c {
+            '\n' => (TokenKind::Newline, 1),
+            '\r' if rest.starts_with("\r\n") => (TokenKind::Newline, 2),
+            c if c.is_whitespace() => (TokenKind::Whitespace, whitespace_len(rest)),
+            '/' if rest.starts_with("//") => (
+                TokenKind::LineComment,
+                rest.find(['\r', '\n']).unwrap_or(rest.len()),
+            ),
+            '/' if rest.starts_with("/*") => match rest.find("*/") {
+                Some(end) => (TokenKind::BlockComment, end + 2),
+                None => {
+                    return Err(RecError::ParseError(
+                        "Unterminated block comment".to_string(),
+                    ));
+                }
+            },
+            '"' => (TokenKind::String, string_len(rest)?),
+            '@' | '#' => (TokenKind::Directive, 1 + ident_len(&rest[1..])),
+            c if c.is_ascii_alphabetic() || c == '_' => (TokenKind::Ident, ident_len(rest)),
+            c if c.is_ascii_digit()
+                || (c == '-' && rest[1..].starts_with(|c: char| c.is_ascii_digit())) =>
+            {
+                (TokenKind::Number, 1 + number_len(&rest[1..]))
+            }
+            '.' if rest.starts_with("...") => (TokenKind::Punct, 3),
+            c => (TokenKind::Punct, c.len_utf8()),
+        };
+        tokens.push(Token::new(kind, &rest[..len]));
+        rest = &rest[len..];
+    }
+    Ok(tokens)
+}
+
+fn whitespace_len(s: &str) -> usize {
+    let mut len = 0;
+    for c in s.chars() {
+        if !c.is_whitespace() || c == '\n' || (c == '\r' && s[len..].starts_with("\r\n")) {
+            break;
+        }
+        len += c.len_utf8();
+    }
+    len
+}
+
+fn ident_len(s: &str) -> usize {
+    s.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(s.len())
+}
+
+/// Numbers run on through letters, digits, `_` and a `.` followed by a
+/// digit, which also covers literals with a suffix, such as `30s`.
+fn number_len(s: &str) -> usize {
+    let mut len = 0;
+    for c in s.chars() {
+        let continues = c.is_ascii_alphanumeric()
+            || c == '_'
+            || (c == '.' && s[len + 1..].starts_with(|c: char| c.is_ascii_digit()));
+        if !continues {
+            break;
+        }
+        len += c.len_utf8();
+    }
+    len
+}
+
+fn string_len(s: &str) -> Result<usize, RecError> {
+    let mut escaped = false;
+    for (i, c) in s.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Ok(i + 1),
+            _ => {}
+        }
+    }
+    Err(RecError::ParseError("Unterminated string".to_string()))
+}
+
+struct SyntaxParser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl SyntaxParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens[self.pos..]
+            .iter()
+            .find(|token| !token.kind.is_trivia())
+    }
+
+    /// The next token, without skipping trivia.
+    fn peek_raw(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn at_punct(&self, punct: &str) -> bool {
+        self.peek().is_some_and(|token| token.is_punct(punct))
+    }
+
+    fn bump(&mut self, node: &mut Node) {
+        node.children
+            .push(Element::Token(self.tokens[self.pos].clone()));
+        self.pos += 1;
+    }
+
+    fn skip_trivia_into(&mut self, node: &mut Node) {
+        while self.peek_raw().is_some_and(|token| token.kind.is_trivia()) {
+            self.bump(node);
+        }
+    }
+
+    fn expect(&mut self, node: &mut Node, kind: TokenKind, what: &str) -> Result<(), RecError> {
+        self.skip_trivia_into(node);
+        match self.peek_raw() {
+            Some(token) if token.kind == kind => {
+                self.bump(node);
+                Ok(())
+            }
+            _ => Err(self.error(&format!("expected {}", what))),
+        }
+    }
+
+    fn expect_punct(&mut self, node: &mut Node, punct: &str) -> Result<(), RecError> {
+        self.skip_trivia_into(node);
+        if self.peek_raw().is_some_and(|token| token.is_punct(punct)) {
+            self.bump(node);
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", punct)))
+        }
+    }
+
+    fn error(&self, message: &str) -> RecError {
+        let line = 1 + self.tokens[..self.pos.min(self.tokens.len())]
+            .iter()
+            .filter(|token| token.kind == TokenKind::Newline)
+            .count();
+        let found = self
+            .peek()
+            .map(|token| format!("'{}'", token.text))
+            .unwrap_or_else(|| "end of input".to_string());
+        RecError::ParseError(format!("line {}: {}, found {}", line, message, found))
+    }
+
+    fn document(&mut self) -> Result<Node, RecError> {
+        let mut doc = Node::new(NodeKind::Document);
+        let mut has_root = false;
+        loop {
+            self.skip_trivia_into(&mut doc);
+            let Some(token) = self.peek_raw() else {
+                break;
+            };
+            let item = match (token.kind, token.text.as_str()) {
+                _ if has_root => return Err(self.error("unexpected trailing input")),
+                (TokenKind::Directive, "#include") => self.include()?,
+                (TokenKind::Directive, "@enum") => self.enum_def()?,
+                (TokenKind::Directive, "@type") => self.type_def()?,
+                (TokenKind::Directive, _) => self.directive()?,
+                (TokenKind::Punct, "{") => {
+                    has_root = true;
+                    self.group(NodeKind::Object, "}", Self::entry)?
+                }
+                _ => return Err(self.error("expected a directive or the root object")),
+            };
+            doc.children.push(Element::Node(item));
+        }
+        if !has_root {
+            return Err(self.error("expected the root object"));
+        }
+        Ok(doc)
+    }
+
+    fn include(&mut self) -> Result<Node, RecError> {
+        let mut node = Node::new(NodeKind::Include);
+        self.bump(&mut node);
+        self.expect(&mut node, TokenKind::String, "an include path")?;
+        Ok(node)
+    }
+
+    fn enum_def(&mut self) -> Result<Node, RecError> {
+        let mut node = Node::new(NodeKind::Enum);
+        self.bump(&mut node);
+        self.expect(&mut node, TokenKind::Ident, "an enum name")?;
+        self.skip_trivia_into(&mut node);
+        let body = self.group(NodeKind::Body, "}", Self::variant)?;
+        node.children.push(Element::Node(body));
+        Ok(node)
+    }
+
+    fn variant(&mut self) -> Result<Node, RecError> {
+        let mut node = Node::new(NodeKind::Variant);
+        self.expect(&mut node, TokenKind::Ident, "a variant name")?;
+        if self.at_punct("{") {
+            self.skip_trivia_into(&mut node);
+            let body = self.group(NodeKind::Body, "}", Self::field)?;
+            node.children.push(Element::Node(body));
+        } else if self.at_punct("(") {
+            self.skip_trivia_into(&mut node);
+            let types = self.group(NodeKind::Tuple, ")", Self::type_expr)?;
+            node.children.push(Element::Node(types));
+        } else if self.at_punct("=") {
+            self.expect_punct(&mut node, "=")?;
+            self.skip_trivia_into(&mut node);
+            let value = self.value()?;
+            node.children.push(Element::Node(value));
+        }
+        Ok(node)
+    }
+
+    fn type_def(&mut self) -> Result<Node, RecError> {
+        let mut node = Node::new(NodeKind::Type);
+        self.bump(&mut node);
+        self.expect(&mut node, TokenKind::Ident, "a type name")?;
+        if self
+            .peek()
+            .is_some_and(|token| token.kind == TokenKind::Ident && token.text == "extends")
+        {
+            self.expect(&mut node, TokenKind::Ident, "extends")?;
+            loop {
+                self.expect(&mut node, TokenKind::Ident, "a base type")?;
+                if !self.at_punct(",") {
+                    break;
+                }
+                self.expect_punct(&mut node, ",")?;
+            }
+        }
+        self.skip_trivia_into(&mut node);
+        let body = self.group(NodeKind::Body, "}", Self::member)?;
+        node.children.push(Element::Node(body));
+        Ok(node)
+    }
+
+    fn member(&mut self) -> Result<Node, RecError> {
+        if self.at_punct("...") {
+            let mut node = Node::new(NodeKind::Spread);
+            self.bump(&mut node);
+            self.expect(&mut node, TokenKind::Ident, "a type name")?;
+            return Ok(node);
+        }
+        self.field()
+    }
+
+    fn field(&mut self) -> Result<Node, RecError> {
+        let mut node = Node::new(NodeKind::Field);
+        self.expect(&mut node, TokenKind::Ident, "a field name")?;
+        if self.peek_raw().is_some_and(|token| token.is_punct("?")) {
+            self.bump(&mut node);
+        }
+        self.expect_punct(&mut node, ":")?;
+        self.skip_trivia_into(&mut node);
+        let ty = self.type_expr()?;
+        node.children.push(Element::Node(ty));
+        Ok(node)
+    }
+
+    /// A type expression runs until a newline, comment, `,` or closing
+    /// bracket outside of any brackets it opened itself.
+    fn type_expr(&mut self) -> Result<Node, RecError> {
+        let mut node = Node::new(NodeKind::TypeExpr);
+        self.balanced_run(&mut node, true)?;
+        if node.children.is_empty() {
+            return Err(self.error("expected a type"));
+        }
+        Ok(node)
+    }
+
+    fn directive(&mut self) -> Result<Node, RecError> {
+        let mut node = Node::new(NodeKind::Directive);
+        self.bump(&mut node);
+        self.balanced_run(&mut node, false)?;
+        Ok(node)
+    }
+
+    /// Takes tokens up to the end of the line, keeping brackets balanced.
+    /// With `stop_at_comma`, a top-level `,` also ends the run.
+    fn balanced_run(&mut self, node: &mut Node, stop_at_comma: bool) -> Result<(), RecError> {
+        let mut depth = 0usize;
+        while let Some(token) = self.peek_raw() {
+            if depth == 0 {
+                let ends = token.kind == TokenKind::Newline
+                    || token.kind.is_comment()
+                    || (stop_at_comma && token.is_punct(","))
+                    || [")", "]", "}"].iter().any(|p| token.is_punct(p));
+                let trailing_space = token.kind == TokenKind::Whitespace
+                    && self.tokens[self.pos + 1..]
+                        .iter()
+                        .find(|t| t.kind != TokenKind::Whitespace)
+                        .is_none_or(|t| {
+                            t.kind.is_trivia()
+                                || (stop_at_comma && t.is_punct(","))
+                                || [")", "]", "}"].iter().any(|p| t.is_punct(p))
+                        });
+                if ends || trailing_space {
+                    break;
+                }
+            }
+            if ["(", "[", "{"].iter().any(|p| token.is_punct(p)) {
+                depth += 1;
+            } else if [")", "]", "}"].iter().any(|p| token.is_punct(p)) {
+                depth -= 1;
+            }
+            self.bump(node);
+        }
+        if depth > 0 {
+            return Err(self.error("unbalanced brackets"));
+        }
+        Ok(())
+    }
+
+    /// A bracketed list: the opening token, items separated by commas or
+    /// newlines, and `close`. Trivia between items belongs to the group.
+    fn group(
+        &mut self,
+        kind: NodeKind,
+        close: &str,
+        mut item: impl FnMut(&mut Self) -> Result<Node, RecError>,
+    ) -> Result<Node, RecError> {
+        let mut node = Node::new(kind);
+        self.bump(&mut node);
+        loop {
+            self.skip_trivia_into(&mut node);
+            match self.peek_raw() {
+                None => return Err(self.error(&format!("expected '{}'", close))),
+                Some(token) if token.is_punct(close) => {
+                    self.bump(&mut node);
+                    return Ok(node);
+                }
+                Some(token) if token.is_punct(",") => self.bump(&mut node),
+                Some(_) => {
+                    let child = item(self)?;
+                    node.children.push(Element::Node(child));
+                }
+            }
+        }
+    }
+
+    fn entry(&mut self) -> Result<Node, RecError> {
+        let mut node = Node::new(NodeKind::Entry);
+        match self.peek_raw() {
+            Some(token) if matches!(token.kind, TokenKind::Ident | TokenKind::String) => {
+                self.bump(&mut node)
+            }
+            _ => return Err(self.error("expected a key")),
+        }
+        self.expect_punct(&mut node, ":")?;
+        self.skip_trivia_into(&mut node);
+        let value = self.value()?;
+        node.children.push(Element::Node(value));
+        Ok(node)
+    }
+
+    fn value(&mut self) -> Result<Node, RecError> {
+        let mut node = Node::new(NodeKind::Value);
+        let Some(token) = self.peek_raw() else {
+            return Err(self.error("expected a value"));
+        };
+        let named = match token.kind {
+            TokenKind::String | TokenKind::Number => {
+                self.bump(&mut node);
+                return Ok(node);
+            }
+            TokenKind::Ident => {
+                self.bump(&mut node);
+                true
+            }
+            TokenKind::Punct if token.text == "." => {
+                self.bump(&mut node);
+                self.expect(&mut node, TokenKind::Ident, "a variant name")?;
+                true
+            }
+            TokenKind::Punct if token.text == "[" => {
+                let array = self.group(NodeKind::Array, "]", Self::value)?;
+                node.children.push(Element::Node(array));
+                return Ok(node);
+            }
+            TokenKind::Punct if token.text == "(" => {
+                let tuple = self.group(NodeKind::Tuple, ")", Self::value)?;
+                node.children.push(Element::Node(tuple));
+                return Ok(node);
+            }
+            TokenKind::Punct if token.text == "{" => {
+                let object = self.group(NodeKind::Object, "}", Self::entry)?;
+                node.children.push(Element::Node(object));
+                return Ok(node);
+            }
+            _ => return Err(self.error("expected a value")),
+        };
+
+        while named
+            && self.peek_raw().is_some_and(|t| t.is_punct("."))
+            && self
+                .tokens
+                .get(self.pos + 1)
+                .is_some_and(|t| t.kind == TokenKind::Ident)
+        {
+            self.bump(&mut node);
+            self.bump(&mut node);
+        }
+
+        // Arguments follow a name directly; an object body may come after
+        // whitespace, as in `Server {` or `Mode.Custom {`.
+        if self.peek_raw().is_some_and(|t| t.is_punct("(")) {
+            let args = self.group(NodeKind::Tuple, ")", Self::value)?;
+            node.children.push(Element::Node(args));
+        } else if self.at_punct("{") {
+            self.skip_trivia_into(&mut node);
+            let object = self.group(NodeKind::Object, "}", Self::entry)?;
+            node.children.push(Element::Node(object));
+        }
+        Ok(node)
+    }
+}
@@ -0,0 +1,187 @@
+//! Duration (`30s`, `1h30m`, `250ms`) and byte size (`512MiB`, `10GB`)
+//! literals.
+
+use std::time::Duration;
+
+/// The units a duration literal is written in, largest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DurationUnit {
+    Days,
+    Hours,
+    Minutes,
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+}
+
+impl DurationUnit {
+    const ALL: [DurationUnit; 7] = [
+        DurationUnit::Days,
+        DurationUnit::Hours,
+        DurationUnit::Minutes,
+        DurationUnit::Seconds,
+        DurationUnit::Milliseconds,
+        DurationUnit::Microseconds,
+        DurationUnit::Nanoseconds,
+    ];
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            DurationUnit::Days => "d",
+            DurationUnit::Hours => "h",
+            DurationUnit::Minutes => "m",
+            DurationUnit::Seconds => "s",
+            DurationUnit::Milliseconds => "ms",
+            DurationUnit::Microseconds => "us",
+            DurationUnit::Nanoseconds => "ns",
+        }
+    }
+
+    pub fn nanos(self) -> u128 {
+        match self {
+            DurationUnit::Days => 86_400_000_000_000,
+            DurationUnit::Hours => 3_600_000_000_000,
+            DurationUnit::Minutes => 60_000_000_000,
+            DurationUnit::Seconds => 1_000_000_000,
+            DurationUnit::Milliseconds => 1_000_000,
+            DurationUnit::Microseconds => 1_000,
+            DurationUnit::Nanoseconds => 1,
+        }
+    }
+}
+
+/// The units a size literal is written in. `KB` is 1000 bytes, `KiB` 1024.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SizeUnit {
+    Bytes,
+    KB,
+    KiB,
+    MB,
+    MiB,
+    GB,
+    GiB,
+    TB,
+    TiB,
+    PB,
+    PiB,
+}
+
+impl SizeUnit {
+    /// Largest first, so the first unit that divides a size exactly is the
+    /// one to print it in.
+    const ALL: [SizeUnit; 11] = [
+        SizeUnit::PiB,
+        SizeUnit::PB,
+        SizeUnit::TiB,
+        SizeUnit::TB,
+        SizeUnit::GiB,
+        SizeUnit::GB,
+        SizeUnit::MiB,
+        SizeUnit::MB,
+        SizeUnit::KiB,
+        SizeUnit::KB,
+        SizeUnit::Bytes,
+    ];
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            SizeUnit::Bytes => "B",
+            SizeUnit::KB => "KB",
+            SizeUnit::KiB => "KiB",
+            SizeUnit::MB => "MB",
+            SizeUnit::MiB => "MiB",
+            SizeUnit::GB => "GB",
+            SizeUnit::GiB => "GiB",
+            SizeUnit::TB => "TB",
+            SizeUnit::TiB => "TiB",
+            SizeUnit::PB => "PB",
+            SizeUnit::PiB => "PiB",
+        }
+    }
+
+    pub fn bytes(self) -> u64 {
+        match self {
+            SizeUnit::Bytes => 1,
+            SizeUnit::KB => 1000,
+            SizeUnit::KiB => 1 << 10,
+            SizeUnit::MB => 1000_u64.pow(2),
+            SizeUnit::MiB => 1 << 20,
+            SizeUnit::GB => 1000_u64.pow(3),
+            SizeUnit::GiB => 1 << 30,
+            SizeUnit::TB => 1000_u64.pow(4),
+            SizeUnit::TiB => 1 << 40,
+            SizeUnit::PB => 1000_u64.pow(5),
+            SizeUnit::PiB => 1 << 50,
+        }
+    }
+}
+
+/// Parses a duration such as `1h30m`: whole numbers, each followed by a
+/// unit, with units from largest to smallest.
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let mut rest = text;
+    let mut total: u128 = 0;
+    let mut smallest: Option<u128> = None;
+    while !rest.is_empty() {
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let (number, tail) = rest.split_at(digits);
+        let letters = tail.len()
+            - tail
+                .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+                .len();
+        let (suffix, tail) = tail.split_at(letters);
+        let unit = DurationUnit::ALL
+            .into_iter()
+            .find(|unit| unit.suffix() == suffix)?;
+        if number.is_empty() || smallest.is_some_and(|s| unit.nanos() >= s) {
+            return None;
+        }
+        smallest = Some(unit.nanos());
+        total = total.checked_add(number.parse::<u128>().ok()?.checked_mul(unit.nanos())?)?;
+        rest = tail;
+    }
+    smallest?;
+    let secs = u64::try_from(total / 1_000_000_000).ok()?;
+    Some(Duration::new(secs, (total % 1_000_000_000) as u32))
+}
+
+/// Writes a duration in the largest units that represent it exactly, as in
+/// `1h30m` or `250ms`. Days are not used.
+pub fn format_duration(duration: Duration) -> String {
+    let mut nanos = duration.as_nanos();
+    if nanos == 0 {
+        return "0s".to_string();
+    }
+    let mut text = String::new();
+    for unit in &DurationUnit::ALL[1..] {
+        let count = nanos / unit.nanos();
+        if count > 0 {
+            text.push_str(&format!("{}{}", count, unit.suffix()));
+            nanos %= unit.nanos();
+        }
+    }
+    text
+}
+
+/// Parses a size such as `512MiB` or `10GB` into bytes.
+pub fn parse_size(text: &str) -> Option<u64> {
+    let digits = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let (number, suffix) = text.split_at(digits);
+    let unit = SizeUnit::ALL
+        .into_iter()
+        .find(|unit| unit.suffix() == suffix)?;
+    if number.is_empty() {
+        return None;
+    }
+    number.parse::<u64>().ok()?.checked_mul(unit.bytes())
+}
+
+/// Writes a size in the largest unit that divides it exactly.
+pub fn format_size(bytes: u64) -> String {
+    let unit = SizeUnit::ALL
+        .into_iter()
+        .find(|unit| bytes != 0 && bytes.is_multiple_of(unit.bytes()))
+        .unwrap_or(SizeUnit::Bytes);
+    format!("{}{}", bytes / unit.bytes(), unit.suffix())
+}
@@ -1,31 +1,159 @@
-use crate::{EnumVariant, EnumVariantData, RecDocument, RecError, RecValue};
-use std::net::SocketAddrV4;
-use std::str::FromStr;
+use crate::value::{parse_date, parse_datetime, parse_time};
+use crate::{
+    ByteEncoding, Cidr, DecimalLimits, DerivedAddress, EnumDef, EnumVariant, EnumVariantData,
+    EvmAddress, FieldDef, IntType, MacAddr, Pda, Pubkey, RecDocument, RecError, RecObject, RecType,
+    RecValue, Secp256k1Pubkey, TimeBounds, TypeDef, UrlPolicy,
+};
+use indexmap::IndexMap;
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
 use url::Url;
 
 pub fn validate(doc: &RecDocument) -> Result<(), RecError> {
-    validate_object(&doc.root, doc)?;
+    for enum_def in doc.enum_definitions.values() {
+        validate_enum_def(enum_def)?;
+    }
+    validate_object(&doc.root, doc, "")?;
     Ok(())
 }
 
-fn validate_object(obj: &crate::RecObject, doc: &RecDocument) -> Result<(), RecError> {
+/// Explicit unit variant values must be unique within an enum and all of
+/// the same kind, so they can stand in for the variant name.
+fn validate_enum_def(enum_def: &EnumDef) -> Result<(), RecError> {
+    let mut seen = HashSet::new();
+    let mut kind = None;
+    for variant in &enum_def.variants {
+        let Some(value) = variant.discriminant() else {
+            continue;
+        };
+        let this_kind = std::mem::discriminant(value);
+        if *kind.get_or_insert(this_kind) != this_kind {
+            return Err(RecError::ValidationError(format!(
+                "Enum {} mixes integer and string variant values",
+                enum_def.name
+            )));
+        }
+        if !seen.insert(value) {
+            return Err(RecError::ValidationError(format!(
+                "Enum {} assigns {} to more than one variant",
+                enum_def.name, value
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn validate_object(obj: &RecObject, doc: &RecDocument, path: &str) -> Result<(), RecError> {
+    if let Some(type_name) = &obj.type_name {
+        let type_def = doc
+            .type_definitions
+            .get(type_name)
+            .ok_or_else(|| RecError::UnknownType(type_name.clone()))?;
+        return validate_fields(&obj.fields, &type_def.fields, type_name, doc, path);
+    }
     for (key, value) in &obj.fields {
-        validate_value(value, doc)?;
+        let path = join_path(path, key);
+        validate_value(value, doc, &path)?;
+        check_untyped_urls(value, &path)?;
     }
     Ok(())
 }
 
-fn validate_value(value: &RecValue, doc: &RecDocument) -> Result<(), RecError> {
+/// Holds `url(..)` values outside any typed field to the policy of a bare
+/// `url` field.
+fn check_untyped_urls(value: &RecValue, path: &str) -> Result<(), RecError> {
     match value {
-        RecValue::Url(u) => validate_url(u)?,
-        RecValue::Socket(s) => validate_socket(s)?,
-        RecValue::Pubkey(p) => validate_pubkey(p)?,
-        RecValue::Array(arr) => {
-            for v in arr {
-                validate_value(v, doc)?;
+        RecValue::Url(u) => check_url_policy(u, &UrlPolicy::default(), path),
+        RecValue::Array(values) => values
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, v)| check_untyped_urls(v, &format!("{}[{}]", path, i))),
+        RecValue::Tuple(values) => values
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, v)| check_untyped_urls(v, &format!("{}.{}", path, i))),
+        _ => Ok(()),
+    }
+}
+
+/// Checks `fields` against a declared field list: every required field is
+/// present, no undeclared field is set, and each value has its field's type.
+fn validate_fields(
+    fields: &IndexMap<String, RecValue>,
+    expected: &IndexMap<String, FieldDef>,
+    owner: &str,
+    doc: &RecDocument,
+    path: &str,
+) -> Result<(), RecError> {
+    for (field_name, field_def) in expected {
+        if !field_def.optional && !fields.contains_key(field_name) {
+            return Err(RecError::MissingField(format!("{}.{}", owner, field_name)));
+        }
+    }
+    for (field_name, value) in fields {
+        let field_path = join_path(path, field_name);
+        let Some(field_def) = expected.get(field_name) else {
+            return Err(RecError::ValidationError(format!(
+                "Unknown field '{}' in {}",
+                field_name, owner
+            )));
+        };
+        if field_def.optional && *value == RecValue::Null {
+            continue;
+        }
+        check_type(value, &field_def.ty, doc, &field_path)?;
+        validate_value(value, doc, &field_path)?;
+    }
+    Ok(())
+}
+
+fn validate_value(value: &RecValue, doc: &RecDocument, path: &str) -> Result<(), RecError> {
+    match value {
+        RecValue::Url(u) => {
+            Url::parse(u)
+                .map_err(|e| RecError::InvalidUrl(format!("'{}' at '{}': {}", u, path, e)))?;
+        }
+        RecValue::Socket(s) => {
+            validate_socket(s, path)?;
+        }
+        RecValue::HostPort(s) => validate_host_port(s, path)?,
+        RecValue::Ip(_)
+        | RecValue::Cidr(_)
+        | RecValue::Mac(_)
+        | RecValue::Pubkey(_)
+        | RecValue::Secp256k1(_)
+        | RecValue::EvmAddress(_) => {
+            validate_address(value, path)?;
+        }
+        RecValue::Date(s) => {
+            parse_date(s).map_err(|e| at_path(e, path))?;
+        }
+        RecValue::Hex(_) | RecValue::Base64(_) => {
+            value.to_bytes().map_err(|e| at_path(e, path))?;
+        }
+        RecValue::Pda(pda) => {
+            derive_pda(pda, path)?;
+        }
+        RecValue::Time(s) => {
+            parse_time(s).map_err(|e| at_path(e, path))?;
+        }
+        RecValue::DateTime(s) => {
+            parse_datetime(s).map_err(|e| at_path(e, path))?;
+        }
+        RecValue::Array(arr) | RecValue::Tuple(arr) => {
+            for (i, v) in arr.iter().enumerate() {
+                validate_value(v, doc, &format!("{}[{}]", path, i))?;
             }
         }
-        RecValue::Object(obj) => validate_object(obj, doc)?,
+        RecValue::Object(obj) => validate_object(obj, doc, path)?,
+        RecValue::EnumVariant {
+            enum_name, variant, ..
+        } if enum_name.is_empty() => {
+            return Err(RecError::ValidationError(format!(
+                "Unknown identifier '{}' at '{}': an enum variant in an untyped position is written Enum.{}",
+                variant, path, variant
+            )));
+        }
         RecValue::EnumVariant {
             enum_name,
             variant,
@@ -33,21 +161,16 @@ fn validate_value(value: &RecValue, doc: &RecDocument) -> Result<(), RecError> {
         } => {
             if let Some(enum_def) = doc.enum_definitions.get(enum_name) {
                 // Find the matching variant definition
-                let variant_def = enum_def
-                    .variants
-                    .iter()
-                    .find(|v| match v {
-                        EnumVariant::Unit(name) => name == variant,
-                        EnumVariant::Tuple(name, _) => name == variant,
-                        EnumVariant::Struct(name, _) => name == variant,
-                    })
-                    .ok_or_else(|| RecError::UnknownEnumVariant {
-                        enum_name: enum_name.clone(),
-                        variant: variant.clone(),
-                    })?;
+                let variant_def =
+                    enum_def
+                        .variant(variant)
+                        .ok_or_else(|| RecError::UnknownEnumVariant {
+                            enum_name: enum_name.clone(),
+                            variant: variant.clone(),
+                        })?;
 
                 match (variant_def, data) {
-                    (EnumVariant::Unit(_), EnumVariantData::Unit) => return Ok(()),
+                    (EnumVariant::Unit(..), EnumVariantData::Unit) => return Ok(()),
                     (EnumVariant::Tuple(_, expected_types), EnumVariantData::Tuple(values)) => {
                         if expected_types.len() != values.len() {
                             return Err(RecError::ValidationError(format!(
@@ -58,30 +181,16 @@ fn validate_value(value: &RecValue, doc: &RecDocument) -> Result<(), RecError> {
                                 values.len()
                             )));
                         }
-                        for value in values {
-                            validate_value(value, doc)?;
+                        for (i, (value, ty)) in values.iter().zip(expected_types).enumerate() {
+                            let value_path = format!("{}.{}", path, i);
+                            check_type(value, ty, doc, &value_path)?;
+                            validate_value(value, doc, &value_path)?;
                         }
                         return Ok(());
                     }
                     (EnumVariant::Struct(_, expected_fields), EnumVariantData::Struct(fields)) => {
-                        for (field_name, field_def) in expected_fields {
-                            if !field_def.optional && !fields.contains_key(field_name) {
-                                return Err(RecError::MissingField(format!(
-                                    "{}.{}.{}",
-                                    enum_name, variant, field_name
-                                )));
-                            }
-                        }
-                        for (field_name, value) in fields {
-                            if !expected_fields.contains_key(field_name) {
-                                return Err(RecError::ValidationError(format!(
-                                    "Unknown field '{}' in {}.{}",
-                                    field_name, enum_name, variant
-                                )));
-                            }
-                            validate_value(value, doc)?;
-                        }
-                        return Ok(());
+                        let owner = format!("{}.{}", enum_name, variant);
+                        return validate_fields(fields, expected_fields, &owner, doc, path);
                     }
                     _ => {
                         return Err(RecError::ValidationError(format!(
@@ -99,43 +208,700 @@ fn validate_value(value: &RecValue, doc: &RecDocument) -> Result<(), RecError> {
     Ok(())
 }
 
-fn validate_url(url: &str) -> Result<(), RecError> {
-    match Url::parse(url) {
-        Ok(u) => {
-            if u.scheme() != "http" && u.scheme() != "https" {
-                return Err(RecError::InvalidUrl(format!(
-                    "URL must be HTTP or HTTPS: {}",
-                    url
+/// Whether `ty` is `of` or composes it, directly or through its bases.
+fn is_subtype(ty: &str, of: &str, doc: &RecDocument) -> bool {
+    ty == of
+        || doc
+            .type_definitions
+            .get(ty)
+            .is_some_and(|def| def.bases.iter().any(|base| is_subtype(base, of, doc)))
+}
+
+/// Checks a single value against its declared type, as `validate` checks
+/// each field.
+pub(crate) fn check_value(
+    value: &RecValue,
+    ty: &RecType,
+    doc: &RecDocument,
+    path: &str,
+) -> Result<(), RecError> {
+    check_type(value, ty, doc, path)?;
+    validate_value(value, doc, path)
+}
+
+/// Checks that `value` has the shape of `ty`. Nested objects and enum
+/// variants are checked against their own definitions by `validate_value`.
+fn check_type(
+    value: &RecValue,
+    ty: &RecType,
+    doc: &RecDocument,
+    path: &str,
+) -> Result<(), RecError> {
+    let matches = match (ty, value) {
+        (RecType::Any, _) => true,
+        // A Loader reads these as the declared type; only a default can be
+        // checked before then.
+        (_, RecValue::Template(_)) => true,
+        (_, RecValue::Env { default, .. }) => {
+            if let Some(default) = default {
+                check_type(default, ty, doc, path)?;
+            }
+            true
+        }
+        (RecType::String, RecValue::String(_))
+        | (RecType::Int, RecValue::Int(_))
+        | (RecType::Float, RecValue::Float(_) | RecValue::Int(_) | RecValue::UInt(_))
+        | (RecType::Bool, RecValue::Bool(_))
+        | (RecType::Socket | RecType::HostPort, RecValue::Socket(_))
+        | (RecType::HostPort, RecValue::HostPort(_))
+        | (RecType::Ip, RecValue::Ip(_))
+        | (RecType::Cidr, RecValue::Cidr(_))
+        | (RecType::Mac, RecValue::Mac(_))
+        | (RecType::Duration, RecValue::Duration(_))
+        | (RecType::Size, RecValue::Size(_))
+        | (RecType::Pubkey(None), RecValue::Pubkey(_))
+        // Keypair files hold ed25519 keys, which are always on the curve.
+        | (RecType::Pubkey(None | Some(true)), RecValue::KeypairPubkey(_))
+        // Program-derived addresses are always off the curve.
+        | (RecType::Pubkey(None | Some(false)), RecValue::Pda(_))
+        | (RecType::Secp256k1, RecValue::Secp256k1(_))
+        | (RecType::EvmAddress, RecValue::EvmAddress(_))
+        | (
+            RecType::Secret,
+            RecValue::Secret(_) | RecValue::SecretRef { .. } | RecValue::Encrypted(_),
+        ) => true,
+        (RecType::Url(policy), RecValue::Url(u)) => {
+            check_url_policy(u, policy, path)?;
+            true
+        }
+        (RecType::Pubkey(Some(on_curve)), RecValue::Pubkey(p)) => {
+            if let Ok(key) = p.parse::<Pubkey>()
+                && key.is_on_curve() != *on_curve
+            {
+                return Err(RecError::InvalidPubkey(format!(
+                    "{} at '{}' is {} the ed25519 curve; {} requires a point {} it",
+                    p,
+                    path,
+                    if *on_curve { "off" } else { "on" },
+                    ty,
+                    if *on_curve { "on" } else { "off" }
                 )));
             }
-            Ok(())
+            true
+        }
+        (RecType::Socket4 | RecType::Socket6, RecValue::Socket(s)) => {
+            let want_v6 = *ty == RecType::Socket6;
+            if let Ok(addr) = validate_socket(s, path)
+                && addr.is_ipv6() != want_v6
+            {
+                return Err(RecError::InvalidSocket(format!(
+                    "'{}' at '{}': expected an {} address",
+                    s,
+                    path,
+                    if want_v6 { "IPv6" } else { "IPv4" }
+                )));
+            }
+            true
+        }
+        (RecType::Decimal(limits), RecValue::Decimal(_) | RecValue::Int(_) | RecValue::UInt(_)) => {
+            check_decimal_limits(value, limits, path)?;
+            true
+        }
+        (RecType::Bytes(encoding, len), RecValue::Hex(_) | RecValue::Base64(_)) => {
+            let written = match value {
+                RecValue::Hex(_) => ByteEncoding::Hex,
+                _ => ByteEncoding::Base64,
+            };
+            if encoding.is_some_and(|encoding| encoding != written) {
+                false
+            } else {
+                if let Some(len) = len
+                    && let Ok(bytes) = value.to_bytes()
+                    && bytes.len() != *len
+                {
+                    return Err(RecError::ValidationError(format!(
+                        "Value at '{}' is {} bytes long; {} requires {}",
+                        path,
+                        bytes.len(),
+                        ty,
+                        len
+                    )));
+                }
+                true
+            }
+        }
+        (RecType::Int, RecValue::UInt(_)) => {
+            check_int_range(value, IntType::I64, path)?;
+            true
+        }
+        (RecType::SizedInt(ty), RecValue::Int(_) | RecValue::UInt(_)) => {
+            check_int_range(value, *ty, path)?;
+            true
+        }
+        (RecType::Date(bounds), RecValue::Date(s)) => {
+            check_bounds(s, bounds, parse_date, path)?;
+            true
+        }
+        (RecType::Time(bounds), RecValue::Time(s)) => {
+            check_bounds(s, bounds, parse_time, path)?;
+            true
+        }
+        (RecType::DateTime(bounds), RecValue::DateTime(s)) => {
+            check_bounds(s, bounds, parse_datetime, path)?;
+            true
+        }
+        (RecType::Array(inner), RecValue::Array(values)) => {
+            for (i, v) in values.iter().enumerate() {
+                check_type(v, inner, doc, &format!("{}[{}]", path, i))?;
+            }
+            true
+        }
+        (RecType::FixedArray(inner, len), RecValue::Array(values)) => {
+            if values.len() != *len {
+                return Err(RecError::ValidationError(format!(
+                    "Array at '{}' expects {} elements, got {}",
+                    path,
+                    len,
+                    values.len()
+                )));
+            }
+            for (i, v) in values.iter().enumerate() {
+                check_type(v, inner, doc, &format!("{}[{}]", path, i))?;
+            }
+            true
+        }
+        (RecType::Tuple(types), RecValue::Tuple(values)) => {
+            if values.len() != types.len() {
+                return Err(RecError::ValidationError(format!(
+                    "Tuple at '{}' expects {} values, got {}",
+                    path,
+                    types.len(),
+                    values.len()
+                )));
+            }
+            for (i, (v, ty)) in values.iter().zip(types).enumerate() {
+                check_type(v, ty, doc, &format!("{}.{}", path, i))?;
+            }
+            true
+        }
+        (RecType::Object(name) | RecType::Enum(name), _)
+            if doc.enum_definitions.contains_key(name) =>
+        {
+            matches!(value, RecValue::EnumVariant { enum_name, .. } if enum_name == name)
+        }
+        (RecType::Object(name), RecValue::Object(obj)) => match &obj.type_name {
+            Some(type_name) => is_subtype(type_name, name, doc),
+            None => {
+                let type_def = doc
+                    .type_definitions
+                    .get(name)
+                    .ok_or_else(|| RecError::UnknownType(name.clone()))?;
+                validate_fields(&obj.fields, &type_def.fields, name, doc, path)?;
+                true
+            }
+        },
+        _ => false,
+    };
+
+    match value {
+        _ if matches => Ok(()),
+        // A shorthand left unresolved sits where no enum is expected, most
+        // likely a misspelled keyword such as `ture`.
+        RecValue::EnumVariant {
+            enum_name, variant, ..
+        } if enum_name.is_empty() => Err(RecError::ValidationError(format!(
+            "Unknown identifier '{}' at '{}': expected {}",
+            variant, path, ty
+        ))),
+        _ => Err(RecError::ValidationError(format!(
+            "Type mismatch at '{}': expected {}, got {}",
+            path,
+            ty,
+            value.type_name()
+        ))),
+    }
+}
+
+/// Fills in the enum name of shorthand variants (`.INFO`, `INFO`) from the
+/// declared type of the field, tuple slot or array they appear in.
+///
+/// Shorthands in an untyped position are left as they are; `validate`
+/// reports them.
+pub fn resolve(doc: &mut RecDocument) {
+    let Ok(()) = for_each_typed_value_mut::<std::convert::Infallible>(doc, &mut |value, ty, _| {
+        if let RecValue::EnumVariant { enum_name, .. } = value
+            && enum_name.is_empty()
+            && let Some((RecType::Object(name) | RecType::Enum(name), true)) = ty
+        {
+            *enum_name = name.clone();
+        }
+        Ok(())
+    });
+}
+
+/// Calls `f` on every value in the document with its path and declared
+/// type, parents before children. The flag says whether the type names an
+/// enum. `f` may replace the value; the walk descends into what it leaves.
+pub(crate) fn for_each_typed_value_mut<E>(
+    doc: &mut RecDocument,
+    f: &mut TypedVisitor<'_, E>,
+) -> Result<(), E> {
+    let RecDocument {
+        root,
+        type_definitions,
+        enum_definitions,
+        ..
+    } = doc;
+    let ctx = TypedWalk {
+        types: type_definitions,
+        enums: enum_definitions,
+    };
+    ctx.walk_object(root, None, "", f)
+}
+
+/// A callback for [`for_each_typed_value_mut`].
+type TypedVisitor<'f, E> =
+    dyn FnMut(&mut RecValue, Option<(&RecType, bool)>, &str) -> Result<(), E> + 'f;
+
+struct TypedWalk<'a> {
+    types: &'a IndexMap<String, TypeDef>,
+    enums: &'a IndexMap<String, EnumDef>,
+}
+
+impl TypedWalk<'_> {
+    fn walk_object<E>(
+        &self,
+        obj: &mut RecObject,
+        ty: Option<&str>,
+        path: &str,
+        f: &mut TypedVisitor<'_, E>,
+    ) -> Result<(), E> {
+        let fields = obj
+            .type_name
+            .as_deref()
+            .or(ty)
+            .and_then(|name| self.types.get(name))
+            .map(|def| &def.fields);
+        self.walk_fields(&mut obj.fields, fields, path, f)
+    }
+
+    fn walk_fields<E>(
+        &self,
+        values: &mut IndexMap<String, RecValue>,
+        fields: Option<&IndexMap<String, FieldDef>>,
+        path: &str,
+        f: &mut TypedVisitor<'_, E>,
+    ) -> Result<(), E> {
+        for (key, value) in values {
+            let ty = fields.and_then(|fields| fields.get(key)).map(|def| &def.ty);
+            self.walk_value(value, ty, &join_path(path, key), f)?;
+        }
+        Ok(())
+    }
+
+    fn walk_value<E>(
+        &self,
+        value: &mut RecValue,
+        ty: Option<&RecType>,
+        path: &str,
+        f: &mut TypedVisitor<'_, E>,
+    ) -> Result<(), E> {
+        let is_enum = matches!(
+            ty,
+            Some(RecType::Object(name) | RecType::Enum(name)) if self.enums.contains_key(name)
+        );
+        f(value, ty.map(|ty| (ty, is_enum)), path)?;
+        match value {
+            RecValue::Array(values) => {
+                let inner = match ty {
+                    Some(RecType::Array(inner) | RecType::FixedArray(inner, _)) => Some(&**inner),
+                    _ => None,
+                };
+                for (i, v) in values.iter_mut().enumerate() {
+                    self.walk_value(v, inner, &format!("{}[{}]", path, i), f)?;
+                }
+            }
+            RecValue::Tuple(values) => {
+                let types = match ty {
+                    Some(RecType::Tuple(types)) => types.as_slice(),
+                    _ => &[],
+                };
+                for (i, v) in values.iter_mut().enumerate() {
+                    self.walk_value(v, types.get(i), &format!("{}.{}", path, i), f)?;
+                }
+            }
+            RecValue::Object(obj) => {
+                let name = match ty {
+                    Some(RecType::Object(name)) => Some(name.as_str()),
+                    _ => None,
+                };
+                self.walk_object(obj, name, path, f)?;
+            }
+            RecValue::Env {
+                default: Some(default),
+                ..
+            } => self.walk_value(default, ty, path, f)?,
+            RecValue::EnumVariant {
+                enum_name,
+                variant,
+                data,
+            } => {
+                let Some(variant_def) = self
+                    .enums
+                    .get(enum_name.as_str())
+                    .and_then(|e| e.variant(variant))
+                else {
+                    return Ok(());
+                };
+                match (variant_def, data) {
+                    (EnumVariant::Tuple(_, types), EnumVariantData::Tuple(values)) => {
+                        for (i, (v, ty)) in values.iter_mut().zip(types).enumerate() {
+                            self.walk_value(v, Some(ty), &format!("{}.{}", path, i), f)?;
+                        }
+                    }
+                    (EnumVariant::Struct(_, fields), EnumVariantData::Struct(values)) => {
+                        self.walk_fields(values, Some(fields), path, f)?;
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
         }
-        Err(_) => Err(RecError::InvalidUrl(url.to_string())),
+        Ok(())
     }
 }
 
-fn validate_socket(addr: &str) -> Result<(), RecError> {
-    match SocketAddrV4::from_str(addr) {
-        Ok(_) => Ok(()),
-        Err(_) => Err(RecError::InvalidSocket(addr.to_string())),
+/// Replaces each `pda(..)` value with the address it derives, recording the
+/// bump seed each one used in `doc.derived_addresses`.
+pub fn derive_addresses(doc: &mut RecDocument) -> Result<(), RecError> {
+    let mut derived = Vec::new();
+    for_each_value_mut(&mut doc.root, &mut |value, path| {
+        let RecValue::Pda(pda) = value else {
+            return Ok(());
+        };
+        let (address, bump) = derive_pda(pda, path)?;
+        derived.push(DerivedAddress {
+            path: path.to_string(),
+            address,
+            bump,
+        });
+        *value = RecValue::Pubkey(address.to_string());
+        Ok(())
+    })?;
+    doc.derived_addresses = derived;
+    Ok(())
+}
+
+fn derive_pda(pda: &Pda, path: &str) -> Result<(Pubkey, u8), RecError> {
+    pda.derive().map_err(|e| match e {
+        RecError::InvalidPubkey(message) => {
+            RecError::InvalidPubkey(format!("{} in pda at '{}'", message, path))
+        }
+        e => e,
+    })
+}
+
+/// Calls `f` on each value under `obj` with its key path, a value before
+/// the values inside it.
+pub(crate) fn for_each_value_mut(
+    obj: &mut RecObject,
+    f: &mut dyn FnMut(&mut RecValue, &str) -> Result<(), RecError>,
+) -> Result<(), RecError> {
+    for (key, value) in &mut obj.fields {
+        visit_value_mut(value, key, f)?;
     }
+    Ok(())
 }
 
-fn validate_pubkey(key: &str) -> Result<(), RecError> {
-    match base58::FromBase58::from_base58(key) {
-        Ok(bytes) => {
-            if bytes.len() != 32 {
-                Err(RecError::InvalidPubkey(format!(
-                    "Invalid pubkey length: expected 32 bytes, got {}",
-                    bytes.len()
-                )))
-            } else {
-                Ok(())
+fn visit_value_mut(
+    value: &mut RecValue,
+    path: &str,
+    f: &mut dyn FnMut(&mut RecValue, &str) -> Result<(), RecError>,
+) -> Result<(), RecError> {
+    f(value, path)?;
+    match value {
+        RecValue::Array(values) => {
+            for (i, v) in values.iter_mut().enumerate() {
+                visit_value_mut(v, &format!("{}[{}]", path, i), f)?;
             }
         }
-        Err(_) => Err(RecError::InvalidPubkey(format!(
-            "Invalid Base58 encoding: {}",
-            key
-        ))),
+        RecValue::Tuple(values)
+        | RecValue::EnumVariant {
+            data: EnumVariantData::Tuple(values),
+            ..
+        } => {
+            for (i, v) in values.iter_mut().enumerate() {
+                visit_value_mut(v, &format!("{}.{}", path, i), f)?;
+            }
+        }
+        RecValue::Object(RecObject { fields, .. })
+        | RecValue::EnumVariant {
+            data: EnumVariantData::Struct(fields),
+            ..
+        } => {
+            for (key, v) in fields {
+                visit_value_mut(v, &join_path(path, key), f)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
     }
 }
+
+/// Checks a URL's scheme, host and path against a `url` field's policy. A
+/// URL that does not parse is left to `validate_value`.
+fn check_url_policy(url: &str, policy: &UrlPolicy, path: &str) -> Result<(), RecError> {
+    let Ok(parsed) = Url::parse(url) else {
+        return Ok(());
+    };
+    let invalid =
+        |reason: String| RecError::InvalidUrl(format!("'{}' at '{}': {}", url, path, reason));
+    if !policy.allows_scheme(parsed.scheme()) {
+        let allowed = if policy.schemes.is_empty() {
+            UrlPolicy::DEFAULT_SCHEMES.join(", ")
+        } else {
+            policy.schemes.join(", ")
+        };
+        return Err(invalid(format!(
+            "scheme '{}' is not one of {}",
+            parsed.scheme(),
+            allowed
+        )));
+    }
+    let has_host = parsed.host_str().is_some_and(|host| !host.is_empty());
+    let has_path = !matches!(parsed.path(), "" | "/");
+    for (name, required, present) in [
+        ("host", policy.host, has_host),
+        ("path", policy.path, has_path),
+    ] {
+        match required {
+            Some(true) if !present => {
+                return Err(invalid(format!(
+                    "{} requires a {}",
+                    RecType::Url(policy.clone()),
+                    name
+                )));
+            }
+            Some(false) if present => {
+                return Err(invalid(format!(
+                    "{} does not allow a {}",
+                    RecType::Url(policy.clone()),
+                    name
+                )));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Splits `host:port` and parses the port. IPv6 hosts must be bracketed and
+/// are returned without the brackets. The error explains what is wrong.
+pub(crate) fn split_host_port(addr: &str) -> Result<(&str, u16), String> {
+    let (host, port) = if let Some(rest) = addr.strip_prefix('[') {
+        let (host, rest) = rest
+            .split_once(']')
+            .ok_or("missing ']' after the IPv6 address")?;
+        let port = rest.strip_prefix(':').ok_or("expected [host]:port")?;
+        (host, port)
+    } else {
+        let (host, port) = addr.rsplit_once(':').ok_or("expected host:port")?;
+        if host.contains(':') {
+            return Err("IPv6 addresses must be in brackets, as in [::1]:8080".to_string());
+        }
+        (host, port)
+    };
+    if host.is_empty() {
+        return Err("missing host before the port".to_string());
+    }
+    let port = port
+        .parse()
+        .map_err(|_| format!("port '{}' is not a number between 0 and 65535", port))?;
+    Ok((host, port))
+}
+
+fn validate_socket(addr: &str, path: &str) -> Result<SocketAddr, RecError> {
+    let invalid =
+        |reason: String| RecError::InvalidSocket(format!("'{}' at '{}': {}", addr, path, reason));
+    let (host, _) = split_host_port(addr).map_err(invalid)?;
+    if host.parse::<IpAddr>().is_err() {
+        return Err(invalid(format!(
+            "'{}' is not an IP address; use a hostport field for host names",
+            host
+        )));
+    }
+    addr.parse()
+        .map_err(|_| invalid("IPv4 addresses must not be bracketed".to_string()))
+}
+
+fn validate_host_port(addr: &str, path: &str) -> Result<(), RecError> {
+    let invalid =
+        |reason: String| RecError::InvalidSocket(format!("'{}' at '{}': {}", addr, path, reason));
+    let (host, _) = split_host_port(addr).map_err(invalid)?;
+    if addr.starts_with('[') {
+        return match host.parse::<std::net::Ipv6Addr>() {
+            Ok(_) => Ok(()),
+            Err(_) => Err(invalid(format!("'{}' is not an IPv6 address", host))),
+        };
+    }
+    if host.parse::<IpAddr>().is_ok() || is_host_name(host) {
+        Ok(())
+    } else {
+        Err(invalid(format!("'{}' is not a valid host name", host)))
+    }
+}
+
+/// A DNS name: dot-separated labels of letters, digits and inner hyphens.
+fn is_host_name(host: &str) -> bool {
+    let host = host.strip_suffix('.').unwrap_or(host);
+    !host.is_empty()
+        && host.len() <= 253
+        && host.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+fn check_decimal_limits(
+    value: &RecValue,
+    limits: &DecimalLimits,
+    path: &str,
+) -> Result<(), RecError> {
+    let decimal = value.to_decimal().map_err(|_| {
+        RecError::ValidationError(format!("Value at '{}' does not fit in a decimal", path))
+    })?;
+    if let Some(max_scale) = limits.scale
+        && decimal.scale() > max_scale
+    {
+        return Err(RecError::ValidationError(format!(
+            "Value {} at '{}' has {} digits after the point; {} allows {}",
+            decimal,
+            path,
+            decimal.scale(),
+            RecType::Decimal(*limits),
+            max_scale
+        )));
+    }
+    if let Some(precision) = limits.precision {
+        let (digits, allowed, what) = match limits.scale {
+            Some(scale) => (
+                decimal.integer_digits(),
+                precision.saturating_sub(scale),
+                " before the point",
+            ),
+            None => (decimal.precision(), precision, ""),
+        };
+        if digits > allowed {
+            return Err(RecError::ValidationError(format!(
+                "Value {} at '{}' has {} digits{}; {} allows {}",
+                decimal,
+                path,
+                digits,
+                what,
+                RecType::Decimal(*limits),
+                allowed
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn check_int_range(value: &RecValue, ty: IntType, path: &str) -> Result<(), RecError> {
+    let (in_range, text) = match value {
+        RecValue::Int(i) => (
+            i128::from(*i) >= ty.min() && u128::try_from(*i).map_or(true, |u| u <= ty.max()),
+            i.to_string(),
+        ),
+        RecValue::UInt(u) => (*u <= ty.max(), u.to_string()),
+        _ => return Ok(()),
+    };
+    if in_range {
+        Ok(())
+    } else {
+        Err(RecError::ValidationError(format!(
+            "Value {} at '{}' is out of range for {} ({}..={})",
+            text,
+            path,
+            ty.name(),
+            ty.min(),
+            ty.max()
+        )))
+    }
+}
+
+/// Checks that `value` falls strictly between the `after` and `before`
+/// bounds. A value that does not parse is left to `validate_value`.
+fn check_bounds<T: PartialOrd>(
+    value: &str,
+    bounds: &TimeBounds,
+    parse: fn(&str) -> Result<T, RecError>,
+    path: &str,
+) -> Result<(), RecError> {
+    let Ok(parsed) = parse(value) else {
+        return Ok(());
+    };
+    for (bound, after) in [(&bounds.after, true), (&bounds.before, false)] {
+        let Some(bound) = bound else { continue };
+        let limit = parse(bound).map_err(|e| {
+            RecError::ValidationError(format!("Invalid bound for '{}': {}", path, e))
+        })?;
+        if (after && parsed <= limit) || (!after && parsed >= limit) {
+            return Err(RecError::ValidationError(format!(
+                "'{}' at '{}' must be {} {}",
+                value,
+                path,
+                if after { "after" } else { "before" },
+                bound
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Adds the key path to a date, time or byte string error.
+fn at_path(error: RecError, path: &str) -> RecError {
+    match error {
+        RecError::InvalidDateTime(message) => {
+            RecError::InvalidDateTime(format!("{} at '{}'", message, path))
+        }
+        RecError::InvalidBytes(message) => {
+            RecError::InvalidBytes(format!("{} at '{}'", message, path))
+        }
+        e => e,
+    }
+}
+
+/// Checks an address or key value such as `ip(..)` or `pubkey(..)`, naming
+/// the path in the error.
+fn validate_address(value: &RecValue, path: &str) -> Result<(), RecError> {
+    let at_path = |message: String| format!("{} at '{}'", message, path);
+    let result = match value {
+        RecValue::Ip(_) => value.to_ip().map(|_| ()),
+        RecValue::Cidr(s) => s.parse::<Cidr>().map(|_| ()),
+        RecValue::Mac(s) => s.parse::<MacAddr>().map(|_| ()),
+        RecValue::Pubkey(s) => s.parse::<Pubkey>().map(|_| ()),
+        RecValue::Secp256k1(s) => s.parse::<Secp256k1Pubkey>().map(|_| ()),
+        RecValue::EvmAddress(s) => s.parse::<EvmAddress>().map(|_| ()),
+        _ => Ok(()),
+    };
+    result.map_err(|e| match e {
+        RecError::InvalidIp(message) => RecError::InvalidIp(at_path(message)),
+        RecError::InvalidCidr(message) => RecError::InvalidCidr(at_path(message)),
+        RecError::InvalidMac(message) => RecError::InvalidMac(at_path(message)),
+        RecError::InvalidPubkey(message) => RecError::InvalidPubkey(at_path(message)),
+        RecError::InvalidSecp256k1Key(message) => RecError::InvalidSecp256k1Key(at_path(message)),
+        RecError::InvalidEvmAddress(message) => RecError::InvalidEvmAddress(at_path(message)),
+        e => e,
+    })
+}
@@ -1,5 +1,15 @@
-use crate::{EnumVariantData, RecError, RecObject, RecValue};
+use crate::units::{self, DurationUnit, SizeUnit};
+use crate::{
+    Cidr, Decimal, EnumDiscriminant, EnumVariantData, EvmAddress, MacAddr, Pubkey, RecDocument,
+    RecError, RecObject, RecValue, Secp256k1Pubkey, Secret,
+};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, Utc};
+use indexmap::IndexMap;
 use serde::Serialize;
+use std::borrow::Cow;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use url::Url;
 
 impl RecValue {
     pub fn as_string(&self) -> Option<&str> {
@@ -29,6 +39,410 @@ impl RecValue {
             _ => None,
         }
     }
+
+    /// Parses a `url(..)` value. Scheme rules are left to the validator.
+    pub fn to_url(&self) -> Result<Url, RecError> {
+        match self {
+            RecValue::Url(u) => {
+                Url::parse(u).map_err(|e| RecError::InvalidUrl(format!("{}: {}", u, e)))
+            }
+            _ => Err(self.type_error("url")),
+        }
+    }
+
+    pub fn to_socket_addr(&self) -> Result<SocketAddr, RecError> {
+        match self {
+            RecValue::Socket(s) => s.parse().map_err(|_| RecError::InvalidSocket(s.clone())),
+            _ => Err(self.type_error("socket")),
+        }
+    }
+
+    /// Splits a `socket(..)` or `hostport(..)` value into its host and port.
+    /// IPv6 hosts are returned without brackets.
+    pub fn to_host_port(&self) -> Result<(String, u16), RecError> {
+        match self {
+            RecValue::Socket(s) | RecValue::HostPort(s) => {
+                let (host, port) = crate::validator::split_host_port(s)
+                    .map_err(|reason| RecError::InvalidSocket(format!("'{}': {}", s, reason)))?;
+                Ok((host.to_string(), port))
+            }
+            _ => Err(self.type_error("hostport")),
+        }
+    }
+
+    pub fn to_ip(&self) -> Result<IpAddr, RecError> {
+        match self {
+            RecValue::Ip(s) => s.parse().map_err(|_| {
+                RecError::InvalidIp(format!("'{}' is not an IPv4 or IPv6 address", s))
+            }),
+            _ => Err(self.type_error("ip")),
+        }
+    }
+
+    pub fn to_cidr(&self) -> Result<Cidr, RecError> {
+        match self {
+            RecValue::Cidr(s) => s.parse(),
+            _ => Err(self.type_error("cidr")),
+        }
+    }
+
+    pub fn to_mac(&self) -> Result<MacAddr, RecError> {
+        match self {
+            RecValue::Mac(s) => s.parse(),
+            _ => Err(self.type_error("mac")),
+        }
+    }
+
+    /// The decoded bytes of a `hex(..)` or `base64(..)` value.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, RecError> {
+        match self {
+            RecValue::Hex(s) => decode_hex(s),
+            RecValue::Base64(s) => decode_base64(s),
+            _ => Err(self.type_error("bytes")),
+        }
+    }
+
+    /// The decoded bytes of a `hex(..)` or `base64(..)` value, which must be
+    /// exactly `N` long.
+    pub fn to_byte_array<const N: usize>(&self) -> Result<[u8; N], RecError> {
+        self.to_bytes()?.try_into().map_err(|bytes: Vec<u8>| {
+            RecError::InvalidBytes(format!("expected {} bytes, got {}", N, bytes.len()))
+        })
+    }
+
+    /// An exact decimal, from a `decimal` or `int` value.
+    pub fn to_decimal(&self) -> Result<Decimal, RecError> {
+        match self {
+            RecValue::Decimal(d) => Ok(*d),
+            RecValue::Int(i) => Ok((*i).into()),
+            RecValue::UInt(u) => Decimal::new(
+                i128::try_from(*u).map_err(|_| self.type_error("decimal"))?,
+                0,
+            ),
+            _ => Err(self.type_error("decimal")),
+        }
+    }
+
+    pub fn to_duration(&self) -> Result<Duration, RecError> {
+        match self {
+            RecValue::Duration(d) => Ok(*d),
+            _ => Err(self.type_error("duration")),
+        }
+    }
+
+    /// The byte count of a size value.
+    pub fn to_size(&self) -> Result<u64, RecError> {
+        match self {
+            RecValue::Size(bytes) => Ok(*bytes),
+            _ => Err(self.type_error("size")),
+        }
+    }
+
+    pub fn to_date(&self) -> Result<NaiveDate, RecError> {
+        match self {
+            RecValue::Date(s) => parse_date(s),
+            _ => Err(self.type_error("date")),
+        }
+    }
+
+    pub fn to_time(&self) -> Result<NaiveTime, RecError> {
+        match self {
+            RecValue::Time(s) => parse_time(s),
+            _ => Err(self.type_error("time")),
+        }
+    }
+
+    /// The timestamp with the UTC offset it was written with.
+    pub fn to_datetime(&self) -> Result<DateTime<FixedOffset>, RecError> {
+        match self {
+            RecValue::DateTime(s) => parse_datetime(s),
+            _ => Err(self.type_error("datetime")),
+        }
+    }
+
+    pub fn to_pubkey(&self) -> Result<Pubkey, RecError> {
+        match self {
+            RecValue::Pubkey(p) => p.parse(),
+            RecValue::Pda(pda) => pda.derive().map(|(address, _)| address),
+            RecValue::KeypairPubkey(file) => Err(RecError::InvalidKeypair(format!(
+                "{} has not been read; parse the document with rec::Loader",
+                file
+            ))),
+            _ => Err(self.type_error("pubkey")),
+        }
+    }
+
+    pub fn to_secp256k1(&self) -> Result<Secp256k1Pubkey, RecError> {
+        match self {
+            RecValue::Secp256k1(k) => k.parse(),
+            _ => Err(self.type_error("secp256k1")),
+        }
+    }
+
+    pub fn to_secret(&self) -> Result<Secret, RecError> {
+        match self {
+            RecValue::Secret(s) => Ok(s.clone()),
+            RecValue::SecretRef { provider, name } => Err(RecError::MissingSecret(format!(
+                "'{}' from '{}' has not been read; parse the document with rec::Loader",
+                name, provider
+            ))),
+            RecValue::Encrypted(_) => Err(RecError::Encryption(
+                "the value has not been decrypted; parse the document with a rec::Loader \
+                 that has a decryption key"
+                    .to_string(),
+            )),
+            _ => Err(self.type_error("secret")),
+        }
+    }
+
+    pub fn to_evm_address(&self) -> Result<EvmAddress, RecError> {
+        match self {
+            RecValue::EvmAddress(a) => a.parse(),
+            _ => Err(self.type_error("evm_address")),
+        }
+    }
+
+    /// The error for an `env(..)` value or `${..}` string that no
+    /// [`Loader`](crate::Loader) has filled in.
+    pub(crate) fn unread_env_error(&self) -> RecError {
+        let value = match self {
+            RecValue::Env { name, .. } => format!("env(\"{}\")", name),
+            _ => format!("'{}'", crate::printer::print_inline(self)),
+        };
+        RecError::EnvVar(format!(
+            "{} has not been read; parse the document with rec::Loader",
+            value
+        ))
+    }
+
+    fn type_error(&self, expected: &str) -> RecError {
+        RecError::TypeError {
+            expected: expected.to_string(),
+            actual: format!("{:?}", self),
+        }
+    }
+
+    /// The name of this value's kind, as used in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            RecValue::String(_) => "string",
+            RecValue::Int(_) | RecValue::UInt(_) => "int",
+            RecValue::Float(_) => "float",
+            RecValue::Decimal(_) => "decimal",
+            RecValue::Hex(_) => "hex",
+            RecValue::Base64(_) => "base64",
+            RecValue::Bool(_) => "bool",
+            RecValue::Null => "null",
+            RecValue::Url(_) => "url",
+            RecValue::Socket(_) => "socket",
+            RecValue::HostPort(_) => "hostport",
+            RecValue::Ip(_) => "ip",
+            RecValue::Cidr(_) => "cidr",
+            RecValue::Mac(_) => "mac",
+            RecValue::Duration(_) => "duration",
+            RecValue::Size(_) => "size",
+            RecValue::Date(_) => "date",
+            RecValue::Time(_) => "time",
+            RecValue::DateTime(_) => "datetime",
+            RecValue::Pubkey(_) => "pubkey",
+            RecValue::Pda(_) => "pda",
+            RecValue::KeypairPubkey(_) => "keypair_pubkey",
+            RecValue::Secp256k1(_) => "secp256k1",
+            RecValue::EvmAddress(_) => "evm_address",
+            RecValue::Secret(_) => "secret",
+            RecValue::SecretRef { .. } => "secret_ref",
+            RecValue::Encrypted(_) => "encrypted",
+            RecValue::Env { .. } => "env",
+            RecValue::Template(_) => "string",
+            RecValue::Array(_) => "array",
+            RecValue::Tuple(_) => "tuple",
+            RecValue::Object(_) => "object",
+            RecValue::EnumVariant { .. } => "enum variant",
+        }
+    }
+}
+
+macro_rules! impl_from {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for RecValue {
+                fn from(value: $ty) -> Self {
+                    RecValue::$variant(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from!(
+    i32 => Int,
+    i64 => Int,
+    u32 => Int,
+    f64 => Float,
+    bool => Bool,
+    &str => String,
+    String => String,
+    Duration => Duration,
+    Vec<RecValue> => Array,
+    RecObject => Object,
+);
+
+impl From<u64> for RecValue {
+    fn from(value: u64) -> Self {
+        i64::try_from(value).map_or(RecValue::UInt(value.into()), RecValue::Int)
+    }
+}
+
+impl From<u128> for RecValue {
+    fn from(value: u128) -> Self {
+        i64::try_from(value).map_or(RecValue::UInt(value), RecValue::Int)
+    }
+}
+
+/// How a unit enum variant is written when serialized.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnitVariantFormat {
+    /// The variant name, in the shape chosen by [`EnumRepr`].
+    #[default]
+    Name,
+    /// The variant's declared value (`20`, `"us-east-1"`) on its own, falling
+    /// back to the name for variants without one. Requires the defining
+    /// document.
+    Value,
+}
+
+/// How enum variants are laid out, mirroring serde's enum representations.
+///
+/// Tuple variants with a single value are written like Rust newtype
+/// variants, as the value itself rather than a one-element array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnumRepr {
+    /// The default: unit variants are an `"Enum.Variant"` string, and
+    /// variants with data are `{"<tag>": "Variant", "<content>": data}`.
+    Tagged {
+        tag: Cow<'static, str>,
+        content: Cow<'static, str>,
+    },
+    /// `{"Variant": data}`; unit variants are a bare `"Variant"` string.
+    External,
+    /// `{"<tag>": "Variant", ..fields}`. Only unit and struct variants, and
+    /// single-value tuple variants holding an object, can be represented.
+    Internal { tag: Cow<'static, str> },
+    /// `{"<tag>": "Variant", "<content>": data}`.
+    Adjacent {
+        tag: Cow<'static, str>,
+        content: Cow<'static, str>,
+    },
+    /// Only the variant's data; unit variants are `null`.
+    Untagged,
+}
+
+/// Options controlling how REC values are serialized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerializeOptions {
+    pub enum_repr: EnumRepr,
+    /// Write variant names as `Enum.Variant` instead of `Variant`.
+    pub enum_prefix: bool,
+    pub unit_variants: UnitVariantFormat,
+    /// Write durations as a number of this unit instead of text like
+    /// `"1h30m"`.
+    pub duration_unit: Option<DurationUnit>,
+    /// Write sizes as a number of this unit instead of text like
+    /// `"512MiB"`.
+    pub size_unit: Option<SizeUnit>,
+    pub decimal_format: DecimalFormat,
+    /// Write `secret(..)` values as they were written instead of
+    /// `"[REDACTED]"`.
+    pub reveal_secrets: bool,
+}
+
+/// How decimals are written when serialized.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DecimalFormat {
+    /// The exact digits, as a string: `"0.000005"`.
+    #[default]
+    String,
+    /// The nearest float. Loses precision beyond about 15 significant
+    /// digits.
+    Number,
+}
+
+pub(crate) const DEFAULT_OPTIONS: SerializeOptions = SerializeOptions {
+    enum_repr: EnumRepr::Tagged {
+        tag: Cow::Borrowed("variant"),
+        content: Cow::Borrowed("data"),
+    },
+    enum_prefix: false,
+    unit_variants: UnitVariantFormat::Name,
+    duration_unit: None,
+    size_unit: None,
+    decimal_format: DecimalFormat::String,
+    reveal_secrets: false,
+};
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        DEFAULT_OPTIONS
+    }
+}
+
+/// A value paired with the options to serialize it with and, optionally,
+/// the document that defines its enums.
+pub struct Serializable<'a, T: ?Sized> {
+    inner: &'a T,
+    doc: Option<&'a RecDocument>,
+    options: &'a SerializeOptions,
+}
+
+impl RecValue {
+    pub fn serialize_with<'a>(
+        &'a self,
+        options: &'a SerializeOptions,
+    ) -> Serializable<'a, RecValue> {
+        Serializable {
+            inner: self,
+            doc: None,
+            options,
+        }
+    }
+}
+
+impl RecDocument {
+    /// Serializes the document's root object with the given options.
+    pub fn serialize_with<'a>(
+        &'a self,
+        options: &'a SerializeOptions,
+    ) -> Serializable<'a, RecObject> {
+        Serializable {
+            inner: &self.root,
+            doc: Some(self),
+            options,
+        }
+    }
+}
+
+impl<'a, T: ?Sized> Serializable<'a, T> {
+    /// Looks up enum definitions, such as unit variant values, in `doc`.
+    pub fn in_document(mut self, doc: &'a RecDocument) -> Self {
+        self.doc = Some(doc);
+        self
+    }
+
+    fn wrap<U: ?Sized>(&self, inner: &'a U) -> Serializable<'a, U> {
+        Serializable {
+            inner,
+            doc: self.doc,
+            options: self.options,
+        }
+    }
+
+    fn discriminant(&self, enum_name: &str, variant: &str) -> Option<&'a EnumDiscriminant> {
+        self.doc?
+            .enum_definitions
+            .get(enum_name)?
+            .variant(variant)?
+            .discriminant()
+    }
 }
 
 impl Serialize for RecValue {
@@ -36,44 +450,272 @@ impl Serialize for RecValue {
     where
         S: serde::Serializer,
     {
-        match self {
+        self.serialize_with(&DEFAULT_OPTIONS).serialize(serializer)
+    }
+}
+
+impl Serialize for Serializable<'_, RecValue> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self.inner {
             RecValue::String(s) => serializer.serialize_str(s),
             RecValue::Int(i) => serializer.serialize_i64(*i),
+            RecValue::UInt(u) => serializer.serialize_u128(*u),
             RecValue::Float(f) => serializer.serialize_f64(*f),
+            RecValue::Decimal(d) => match self.options.decimal_format {
+                DecimalFormat::String => serializer.collect_str(d),
+                DecimalFormat::Number => serializer.serialize_f64(d.to_f64()),
+            },
             RecValue::Bool(b) => serializer.serialize_bool(*b),
             RecValue::Null => serializer.serialize_none(),
             RecValue::Url(u) => serializer.serialize_str(u),
-            RecValue::Socket(s) => serializer.serialize_str(s),
-            RecValue::Pubkey(p) => serializer.serialize_str(p),
-            RecValue::Array(arr) => arr.serialize(serializer),
-            RecValue::Object(obj) => obj.fields.serialize(serializer),
+            RecValue::Socket(s)
+            | RecValue::HostPort(s)
+            | RecValue::Ip(s)
+            | RecValue::Cidr(s)
+            | RecValue::Mac(s)
+            | RecValue::Date(s)
+            | RecValue::Time(s)
+            | RecValue::DateTime(s)
+            | RecValue::Hex(s)
+            | RecValue::Base64(s) => serializer.serialize_str(s),
+            RecValue::Pubkey(p) | RecValue::Secp256k1(p) | RecValue::EvmAddress(p) => {
+                serializer.serialize_str(p)
+            }
+            RecValue::Secret(s) if self.options.reveal_secrets => {
+                serializer.serialize_str(s.expose_secret())
+            }
+            RecValue::Secret(s) => s.serialize(serializer),
+            // Values that were never read or decrypted are redacted like any
+            // other secret, and only fail when they would be revealed.
+            RecValue::SecretRef { .. } | RecValue::Encrypted(_) if self.options.reveal_secrets => {
+                let secret = self.inner.to_secret().map_err(serde::ser::Error::custom)?;
+                serializer.serialize_str(secret.expose_secret())
+            }
+            RecValue::SecretRef { .. } | RecValue::Encrypted(_) => {
+                serializer.serialize_str(crate::secret::REDACTED)
+            }
+            // Outside a Loader, as written.
+            RecValue::Template(s) => serializer.serialize_str(s),
+            RecValue::Env { .. } => {
+                serializer.serialize_str(&crate::printer::print_inline(self.inner))
+            }
+            RecValue::Pda(_) | RecValue::KeypairPubkey(_) => {
+                let address = self.inner.to_pubkey().map_err(serde::ser::Error::custom)?;
+                serializer.collect_str(&address)
+            }
+            RecValue::Duration(d) => match self.options.duration_unit {
+                Some(unit) => serialize_quantity(d.as_nanos(), unit.nanos(), serializer),
+                None => serializer.serialize_str(&units::format_duration(*d)),
+            },
+            RecValue::Size(bytes) => match self.options.size_unit {
+                Some(unit) => serialize_quantity((*bytes).into(), unit.bytes().into(), serializer),
+                None => serializer.serialize_str(&units::format_size(*bytes)),
+            },
+            RecValue::Array(arr) => self.wrap(arr.as_slice()).serialize(serializer),
+            RecValue::Tuple(values) => {
+                use serde::ser::SerializeTuple;
+                let mut tuple = serializer.serialize_tuple(values.len())?;
+                for value in values {
+                    tuple.serialize_element(&self.wrap(value))?;
+                }
+                tuple.end()
+            }
+            RecValue::Object(obj) => self.wrap(&obj.fields).serialize(serializer),
             RecValue::EnumVariant {
                 enum_name,
                 variant,
                 data,
-            } => match data {
-                EnumVariantData::Unit => {
-                    serializer.serialize_str(&format!("{}.{}", enum_name, variant))
-                }
-                EnumVariantData::Tuple(values) => {
-                    use serde::ser::SerializeMap;
-                    let mut map = serializer.serialize_map(Some(2))?;
-                    map.serialize_entry("variant", &format!("{}.{}", enum_name, variant))?;
-                    map.serialize_entry("data", values)?;
-                    map.end()
+            } => self.serialize_variant(enum_name, variant, data, serializer),
+        }
+    }
+}
+
+/// Decodes hex digits, upper or lower case, with an optional `0x` prefix.
+pub(crate) fn decode_hex(s: &str) -> Result<Vec<u8>, RecError> {
+    hex::decode(s.strip_prefix("0x").unwrap_or(s))
+        .map_err(|e| RecError::InvalidBytes(format!("'{}' is not valid hex ({})", s, e)))
+}
+
+/// Decodes standard base64, with or without `=` padding.
+pub(crate) fn decode_base64(s: &str) -> Result<Vec<u8>, RecError> {
+    use base64::Engine;
+    use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+
+    const ENGINE: GeneralPurpose = GeneralPurpose::new(
+        &base64::alphabet::STANDARD,
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+    );
+    ENGINE
+        .decode(s)
+        .map_err(|e| RecError::InvalidBytes(format!("'{}' is not valid base64 ({})", s, e)))
+}
+
+/// Parses an RFC 3339 `full-date`.
+pub(crate) fn parse_date(s: &str) -> Result<NaiveDate, RecError> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map_err(|e| RecError::InvalidDateTime(format!("'{}' is not a date ({})", s, e)))
+}
+
+/// Parses an RFC 3339 `partial-time`, with optional fractional seconds.
+pub(crate) fn parse_time(s: &str) -> Result<NaiveTime, RecError> {
+    NaiveTime::parse_from_str(s, "%H:%M:%S%.f")
+        .map_err(|e| RecError::InvalidDateTime(format!("'{}' is not a time ({})", s, e)))
+}
+
+/// Parses an RFC 3339 `date-time`, which must carry a UTC offset or `Z`.
+pub(crate) fn parse_datetime(s: &str) -> Result<DateTime<FixedOffset>, RecError> {
+    DateTime::parse_from_rfc3339(s)
+        .map_err(|e| RecError::InvalidDateTime(format!("'{}' is not a datetime ({})", s, e)))
+}
+
+/// Writes `amount / unit` as an integer when it divides exactly and as a
+/// float otherwise.
+fn serialize_quantity<S>(amount: u128, unit: u128, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match u64::try_from(amount / unit) {
+        Ok(whole) if amount.is_multiple_of(unit) => serializer.serialize_u64(whole),
+        _ => serializer.serialize_f64(amount as f64 / unit as f64),
+    }
+}
+
+impl Serializable<'_, RecValue> {
+    fn serialize_variant<S>(
+        &self,
+        enum_name: &str,
+        variant: &str,
+        data: &EnumVariantData,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::{Error, SerializeMap};
+
+        if let EnumVariantData::Unit = data
+            && self.options.unit_variants == UnitVariantFormat::Value
+            && let Some(value) = self.discriminant(enum_name, variant)
+        {
+            return value.serialize(serializer);
+        }
+
+        let name = if self.options.enum_prefix {
+            format!("{}.{}", enum_name, variant)
+        } else {
+            variant.to_string()
+        };
+        let content = VariantContent { data, of: self };
+
+        match (&self.options.enum_repr, data) {
+            (EnumRepr::Tagged { .. }, EnumVariantData::Unit) => {
+                serializer.serialize_str(&format!("{}.{}", enum_name, variant))
+            }
+            (EnumRepr::External, EnumVariantData::Unit) => serializer.serialize_str(&name),
+            (EnumRepr::External, _) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(&name, &content)?;
+                map.end()
+            }
+            (EnumRepr::Internal { tag }, EnumVariantData::Unit) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(tag.as_ref(), &name)?;
+                map.end()
+            }
+            (EnumRepr::Internal { tag }, EnumVariantData::Struct(fields)) => {
+                let mut map = serializer.serialize_map(Some(fields.len() + 1))?;
+                map.serialize_entry(tag.as_ref(), &name)?;
+                for (k, v) in fields {
+                    map.serialize_entry(k, &self.wrap(v))?;
                 }
-                EnumVariantData::Struct(fields) => {
-                    use serde::ser::SerializeMap;
-                    let mut map = serializer.serialize_map(Some(2))?;
-                    map.serialize_entry("variant", &format!("{}.{}", enum_name, variant))?;
-                    map.serialize_entry("data", fields)?;
+                map.end()
+            }
+            (EnumRepr::Internal { tag }, EnumVariantData::Tuple(values)) => match values.as_slice()
+            {
+                [RecValue::Object(obj)] => {
+                    let mut map = serializer.serialize_map(Some(obj.fields.len() + 1))?;
+                    map.serialize_entry(tag.as_ref(), &name)?;
+                    for (k, v) in &obj.fields {
+                        map.serialize_entry(k, &self.wrap(v))?;
+                    }
                     map.end()
                 }
+                _ => Err(S::Error::custom(format!(
+                    "internally tagged enums cannot represent tuple variant {}.{}",
+                    enum_name, variant
+                ))),
+            },
+            (EnumRepr::Adjacent { tag, .. }, EnumVariantData::Unit) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(tag.as_ref(), &name)?;
+                map.end()
+            }
+            (
+                EnumRepr::Tagged { tag, content: key } | EnumRepr::Adjacent { tag, content: key },
+                _,
+            ) => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry(tag.as_ref(), &name)?;
+                map.serialize_entry(key.as_ref(), &content)?;
+                map.end()
+            }
+            (EnumRepr::Untagged, _) => content.serialize(serializer),
+        }
+    }
+}
+
+/// The data carried by an enum variant, without its name.
+struct VariantContent<'a, 'b> {
+    data: &'a EnumVariantData,
+    of: &'b Serializable<'a, RecValue>,
+}
+
+impl Serialize for VariantContent<'_, '_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self.data {
+            EnumVariantData::Unit => serializer.serialize_unit(),
+            EnumVariantData::Tuple(values) => match values.as_slice() {
+                [value] => self.of.wrap(value).serialize(serializer),
+                values => self.of.wrap(values).serialize(serializer),
             },
+            EnumVariantData::Struct(fields) => self.of.wrap(fields).serialize(serializer),
         }
     }
 }
 
+impl Serialize for Serializable<'_, [RecValue]> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.inner.iter().map(|v| self.wrap(v)))
+    }
+}
+
+impl Serialize for Serializable<'_, IndexMap<String, RecValue>> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_map(self.inner.iter().map(|(k, v)| (k, self.wrap(v))))
+    }
+}
+
+impl Serialize for Serializable<'_, RecObject> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.wrap(&self.inner.fields).serialize(serializer)
+    }
+}
+
 pub trait RecDeserialize: Sized {
     fn from_rec(value: &RecValue) -> Result<Self, RecError>;
 }
@@ -102,6 +744,31 @@ impl RecDeserialize for i64 {
     }
 }
 
+macro_rules! impl_rec_deserialize_int {
+    ($($ty:ty),*) => {$(
+        impl RecDeserialize for $ty {
+            fn from_rec(value: &RecValue) -> Result<Self, RecError> {
+                let converted = match value {
+                    RecValue::Int(i) => <$ty>::try_from(*i).ok(),
+                    RecValue::UInt(u) => <$ty>::try_from(*u).ok(),
+                    // Sizes are byte counts, read only into types that hold
+                    // any of them.
+                    RecValue::Size(bytes) if <$ty>::BITS >= u64::BITS => {
+                        <$ty>::try_from(*bytes).ok()
+                    }
+                    _ => None,
+                };
+                converted.ok_or_else(|| RecError::TypeError {
+                    expected: stringify!($ty).to_string(),
+                    actual: format!("{:?}", value),
+                })
+            }
+        }
+    )*};
+}
+
+impl_rec_deserialize_int!(i8, i16, i32, u8, u16, u32, u64, u128);
+
 impl RecDeserialize for bool {
     fn from_rec(value: &RecValue) -> Result<Self, RecError> {
         match value {
@@ -114,6 +781,96 @@ impl RecDeserialize for bool {
     }
 }
 
+impl RecDeserialize for Url {
+    fn from_rec(value: &RecValue) -> Result<Self, RecError> {
+        value.to_url()
+    }
+}
+
+impl RecDeserialize for SocketAddr {
+    fn from_rec(value: &RecValue) -> Result<Self, RecError> {
+        value.to_socket_addr()
+    }
+}
+
+impl RecDeserialize for IpAddr {
+    fn from_rec(value: &RecValue) -> Result<Self, RecError> {
+        value.to_ip()
+    }
+}
+
+impl RecDeserialize for Cidr {
+    fn from_rec(value: &RecValue) -> Result<Self, RecError> {
+        value.to_cidr()
+    }
+}
+
+impl RecDeserialize for MacAddr {
+    fn from_rec(value: &RecValue) -> Result<Self, RecError> {
+        value.to_mac()
+    }
+}
+
+impl RecDeserialize for Decimal {
+    fn from_rec(value: &RecValue) -> Result<Self, RecError> {
+        value.to_decimal()
+    }
+}
+
+impl RecDeserialize for Duration {
+    fn from_rec(value: &RecValue) -> Result<Self, RecError> {
+        value.to_duration()
+    }
+}
+
+impl RecDeserialize for NaiveDate {
+    fn from_rec(value: &RecValue) -> Result<Self, RecError> {
+        value.to_date()
+    }
+}
+
+impl RecDeserialize for NaiveTime {
+    fn from_rec(value: &RecValue) -> Result<Self, RecError> {
+        value.to_time()
+    }
+}
+
+impl RecDeserialize for DateTime<FixedOffset> {
+    fn from_rec(value: &RecValue) -> Result<Self, RecError> {
+        value.to_datetime()
+    }
+}
+
+impl RecDeserialize for DateTime<Utc> {
+    fn from_rec(value: &RecValue) -> Result<Self, RecError> {
+        value.to_datetime().map(|dt| dt.with_timezone(&Utc))
+    }
+}
+
+impl RecDeserialize for Pubkey {
+    fn from_rec(value: &RecValue) -> Result<Self, RecError> {
+        value.to_pubkey()
+    }
+}
+
+impl RecDeserialize for Secp256k1Pubkey {
+    fn from_rec(value: &RecValue) -> Result<Self, RecError> {
+        value.to_secp256k1()
+    }
+}
+
+impl RecDeserialize for EvmAddress {
+    fn from_rec(value: &RecValue) -> Result<Self, RecError> {
+        value.to_evm_address()
+    }
+}
+
+impl RecDeserialize for Secret {
+    fn from_rec(value: &RecValue) -> Result<Self, RecError> {
+        value.to_secret()
+    }
+}
+
 impl<T: RecDeserialize> RecDeserialize for Vec<T> {
     fn from_rec(value: &RecValue) -> Result<Self, RecError> {
         match value {
@@ -121,6 +878,12 @@ impl<T: RecDeserialize> RecDeserialize for Vec<T> {
                 .iter()
                 .map(|v| T::from_rec(v))
                 .collect::<Result<Vec<_>, _>>(),
+            // Byte strings read as arrays of their bytes.
+            RecValue::Hex(_) | RecValue::Base64(_) => value
+                .to_bytes()?
+                .into_iter()
+                .map(|b| T::from_rec(&RecValue::Int(b.into())))
+                .collect(),
             _ => Err(RecError::TypeError {
                 expected: "array".to_string(),
                 actual: format!("{:?}", value),
@@ -137,3 +900,40 @@ impl<T: RecDeserialize> RecDeserialize for Option<T> {
         }
     }
 }
+
+impl<T: RecDeserialize, const N: usize> RecDeserialize for [T; N] {
+    fn from_rec(value: &RecValue) -> Result<Self, RecError> {
+        let items = Vec::<T>::from_rec(value)?;
+        let len = items.len();
+        items.try_into().map_err(|_| RecError::TypeError {
+            expected: format!("array of {} elements", N),
+            actual: format!("array of {} elements", len),
+        })
+    }
+}
+
+macro_rules! impl_rec_deserialize_tuple {
+    ($len:literal => $($name:ident),+) => {
+        impl<$($name: RecDeserialize),+> RecDeserialize for ($($name,)+) {
+            fn from_rec(value: &RecValue) -> Result<Self, RecError> {
+                match value {
+                    RecValue::Tuple(values) if values.len() == $len => {
+                        let mut values = values.iter();
+                        Ok(($($name::from_rec(values.next().unwrap())?,)+))
+                    }
+                    _ => Err(RecError::TypeError {
+                        expected: format!("tuple of {} values", $len),
+                        actual: format!("{:?}", value),
+                    }),
+                }
+            }
+        }
+    };
+}
+
+impl_rec_deserialize_tuple!(1 => A);
+impl_rec_deserialize_tuple!(2 => A, B);
+impl_rec_deserialize_tuple!(3 => A, B, C);
+impl_rec_deserialize_tuple!(4 => A, B, C, D);
+impl_rec_deserialize_tuple!(5 => A, B, C, D, E);
+impl_rec_deserialize_tuple!(6 => A, B, C, D, E, F);
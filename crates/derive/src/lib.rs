@@ -4,12 +4,18 @@ use syn::{parse_macro_input, Data, DeriveInput, LitStr};
 
 /// Load and parse REC files at runtime
 ///
+/// The path is read relative to the current directory.
+///
 /// # Example
 /// ```rust
 /// use rec_macros::rec;
 ///
-/// let config = rec!("config/app.rec");
-/// let port = config.get("server")?.get("port")?.as_int()?;
+/// # fn main() -> Result<(), String> {
+/// let config = rec!("../../examples/basic.rec");
+/// let port = config.root.fields["server"].get("port").and_then(|p| p.as_int());
+/// assert_eq!(port, Some(8080));
+/// # Ok(())
+/// # }
 /// ```
 #[proc_macro]
 pub fn rec(input: TokenStream) -> TokenStream {
@@ -33,15 +39,18 @@ pub fn rec(input: TokenStream) -> TokenStream {
 
 /// Load and parse REC files at compile time
 ///
+/// Like `include_str!`, the path is relative to the file that invokes
+/// the macro.
+///
 /// # Example
 /// ```rust
 /// use rec_macros::rec_const;
 ///
-/// static CONFIG: &str = rec_const!("config/app.rec");
+/// static CONFIG: &str = rec_const!("../../../examples/basic.rec");
 ///
 /// fn main() {
 ///     let doc = rec::parse_rec(CONFIG).unwrap();
-///     // Use the pre-validated config
+///     assert!(doc.root.fields.contains_key("server"));
 /// }
 /// ```
 #[proc_macro]
@@ -77,30 +86,86 @@ pub fn rec_const(input: TokenStream) -> TokenStream {
 /// struct ServerConfig {
 ///     host: String,
 ///     port: u16,
-///     #[rec(default = true)]
+///     #[serde(default)]
 ///     ssl_enabled: bool,
-///     #[rec(rename = "allowed_origins")]
+///     #[serde(rename = "allowed_origins")]
 ///     origins: Vec<String>,
 /// }
 ///
 /// #[derive(RecParse, Deserialize, Serialize)]
+/// struct AppConfig {
+///     app_name: String,
+///     server: ServerConfig,
+/// }
+///
+/// #[derive(RecParse, Deserialize, Serialize)]
 /// #[rec(enum_type = "tagged")]
+/// enum Environment {
+///     DEVELOPMENT,
+///     STAGING,
+///     PRODUCTION,
+/// }
+///
+/// #[derive(RecParse, Deserialize, Serialize)]
+/// #[serde(tag = "kind")]
+/// #[rec(enum_type = "internal", tag = "kind")]
 /// enum Database {
 ///     Postgres { host: String, port: u16 },
 ///     Redis { host: String, port: u16 },
 /// }
 ///
-/// let config = ServerConfig::from_rec_file("server.rec")?;
-/// let config = ServerConfig::from_rec_value(&rec_value)?;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let config = AppConfig::from_rec_file("../../examples/basic.rec")?;
+/// assert_eq!(config.server.port, 8080);
+///
+/// let doc = rec::parse_rec(r#"
+///     @enum Environment { DEVELOPMENT, STAGING, PRODUCTION }
+///     { environment: Environment.STAGING }
+/// "#)?;
+/// let environment = Environment::from_rec_value(&doc.root.fields["environment"])?;
+/// assert!(matches!(environment, Environment::STAGING));
+///
+/// let doc = rec::parse_rec(r#"
+///     @enum Database {
+///         Postgres { host: string, port: int }
+///         Redis { host: string, port: int }
+///     }
+///     { db: Database.Redis { host: "localhost", port: 6379 } }
+/// "#)?;
+/// let db = Database::from_rec_value(&doc.root.fields["db"])?;
+/// assert!(matches!(db, Database::Redis { port: 6379, .. }));
+/// # Ok(())
+/// # }
 /// ```
+///
+/// `from_rec_file` and `from_rec_str` read documents through
+/// `rec::Loader`, so `env(..)`, `keypair_pubkey(..)` and the other loaded
+/// values are evaluated, with relative paths in a file resolved against its
+/// directory.
+///
+/// The container attribute `#[rec(..)]` selects how enum variants are
+/// handed to serde, and should match the type's `#[serde(..)]` enum
+/// representation:
+///
+/// - `enum_type = "tagged" | "external" | "internal" | "adjacent" | "untagged"`
+///   (default `"tagged"`: unit variants as `"Enum.Variant"` strings, other
+///   variants as `{"variant": .., "data": ..}`)
+/// - `tag = ".."` and `content = ".."` name the keys for the tagged,
+///   internal and adjacent representations (default `"variant"` and
+///   `"data"`)
+/// - `enum_prefix` writes variant names as `Enum.Variant`
 #[proc_macro_derive(RecParse, attributes(rec))]
 pub fn derive_rec_parse(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = &input.ident;
+    let options = match serialize_options(&input.attrs) {
+        Ok(options) => options,
+        Err(e) => return TokenStream::from(e.to_compile_error()),
+    };
 
     let implementation = match &input.data {
-        Data::Struct(data_struct) => generate_struct_impl(name, data_struct),
-        Data::Enum(data_enum) => generate_enum_impl(name, data_enum),
+        Data::Struct(data_struct) => generate_struct_impl(name, data_struct, &options),
+        Data::Enum(data_enum) => generate_enum_impl(name, data_enum, &options),
         Data::Union(_) => {
             return TokenStream::from(
                 syn::Error::new_spanned(&input, "RecParse cannot be derived for unions")
@@ -112,19 +177,17 @@ pub fn derive_rec_parse(input: TokenStream) -> TokenStream {
     let expanded = quote! {
         impl #name {
             pub fn from_rec_file<P: AsRef<::std::path::Path>>(path: P) -> Result<Self, Box<dyn ::std::error::Error>> {
-                let content = ::std::fs::read_to_string(path)?;
-                let doc = ::rec::parse_rec(&content)?;
+                let doc = ::rec::Loader::new().load(path)?;
                 Self::from_rec_value(&::rec::RecValue::Object(doc.root))
             }
 
             pub fn from_rec_value(value: &::rec::RecValue) -> Result<Self, Box<dyn ::std::error::Error>> {
-                let json = ::serde_json::to_value(value)?;
-                let result = ::serde_json::from_value(json)?;
-                Ok(result)
+                let options = #options;
+                Ok(::rec::from_value_with(value, &options)?)
             }
 
             pub fn from_rec_str(content: &str) -> Result<Self, Box<dyn ::std::error::Error>> {
-                let doc = ::rec::parse_rec(content)?;
+                let doc = ::rec::Loader::new().parse(content)?;
                 Self::from_rec_value(&::rec::RecValue::Object(doc.root))
             }
         }
@@ -135,19 +198,82 @@ pub fn derive_rec_parse(input: TokenStream) -> TokenStream {
     TokenStream::from(expanded)
 }
 
+/// Builds the `SerializeOptions` expression selected by the container's
+/// `#[rec(..)]` attributes.
+fn serialize_options(attrs: &[syn::Attribute]) -> syn::Result<proc_macro2::TokenStream> {
+    let mut enum_type = None;
+    let mut tag = None;
+    let mut content = None;
+    let mut enum_prefix = false;
+
+    for attr in attrs.iter().filter(|a| a.path().is_ident("rec")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("enum_type") {
+                enum_type = Some(meta.value()?.parse::<LitStr>()?);
+            } else if meta.path.is_ident("tag") {
+                tag = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("content") {
+                content = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("enum_prefix") {
+                enum_prefix = true;
+            } else {
+                return Err(meta.error("unsupported rec attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    let tag = tag.unwrap_or_else(|| "variant".to_string());
+    let content = content.unwrap_or_else(|| "data".to_string());
+    let enum_repr = match enum_type.as_ref().map(LitStr::value).as_deref() {
+        Some("external") => quote! { ::rec::value::EnumRepr::External },
+        Some("internal") => quote! {
+            ::rec::value::EnumRepr::Internal {
+                tag: ::std::borrow::Cow::Borrowed(#tag),
+            }
+        },
+        Some("tagged") | None => quote! {
+            ::rec::value::EnumRepr::Tagged {
+                tag: ::std::borrow::Cow::Borrowed(#tag),
+                content: ::std::borrow::Cow::Borrowed(#content),
+            }
+        },
+        Some("adjacent") => quote! {
+            ::rec::value::EnumRepr::Adjacent {
+                tag: ::std::borrow::Cow::Borrowed(#tag),
+                content: ::std::borrow::Cow::Borrowed(#content),
+            }
+        },
+        Some("untagged") => quote! { ::rec::value::EnumRepr::Untagged },
+        Some(_) => {
+            return Err(syn::Error::new_spanned(
+                enum_type,
+                "expected \"tagged\", \"external\", \"internal\", \"adjacent\" or \"untagged\"",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        ::rec::value::SerializeOptions {
+            enum_repr: #enum_repr,
+            enum_prefix: #enum_prefix,
+            ..::std::default::Default::default()
+        }
+    })
+}
+
 fn generate_struct_impl(
     name: &syn::Ident,
     _data_struct: &syn::DataStruct,
+    options: &proc_macro2::TokenStream,
 ) -> proc_macro2::TokenStream {
     quote! {
         impl ::rec::RecDeserialize for #name {
             fn from_rec(value: &::rec::RecValue) -> Result<Self, ::rec::RecError> {
                 match value {
-                    ::rec::RecValue::Object(obj) => {
-                        let json = ::serde_json::to_value(value)
-                            .map_err(|e| ::rec::RecError::ParseError(e.to_string()))?;
-                        ::serde_json::from_value(json)
-                            .map_err(|e| ::rec::RecError::ParseError(e.to_string()))
+                    ::rec::RecValue::Object(_) => {
+                        let options = #options;
+                        ::rec::from_value_with(value, &options)
                     }
                     _ => Err(::rec::RecError::TypeError {
                         expected: "object".to_string(),
@@ -159,16 +285,18 @@ fn generate_struct_impl(
     }
 }
 
-fn generate_enum_impl(name: &syn::Ident, _data_enum: &syn::DataEnum) -> proc_macro2::TokenStream {
+fn generate_enum_impl(
+    name: &syn::Ident,
+    _data_enum: &syn::DataEnum,
+    options: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
     quote! {
         impl ::rec::RecDeserialize for #name {
             fn from_rec(value: &::rec::RecValue) -> Result<Self, ::rec::RecError> {
                 match value {
-                    ::rec::RecValue::EnumVariant { enum_name, variant, data } => {
-                        let json = ::serde_json::to_value(value)
-                            .map_err(|e| ::rec::RecError::ParseError(e.to_string()))?;
-                        ::serde_json::from_value(json)
-                            .map_err(|e| ::rec::RecError::ParseError(e.to_string()))
+                    ::rec::RecValue::EnumVariant { .. } => {
+                        let options = #options;
+                        ::rec::from_value_with(value, &options)
                     }
                     _ => Err(::rec::RecError::TypeError {
                         expected: "enum variant".to_string(),
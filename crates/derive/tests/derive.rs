@@ -0,0 +1,38 @@
+use rec_macros::RecParse;
+use serde::Deserialize;
+
+#[derive(RecParse, Deserialize)]
+struct Signer {
+    authority: String,
+}
+
+#[test]
+fn test_from_rec_file_resolves_relative_paths() {
+    let dir = std::env::temp_dir().join(format!("rec-derive-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("keys")).unwrap();
+    // A 32-byte ed25519 secret of 7s followed by its public key.
+    let keypair: [u8; 64] = [
+        7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7,
+        7, 7, 234, 74, 108, 99, 226, 156, 82, 10, 190, 245, 80, 123, 19, 46, 197, 249, 149, 71,
+        118, 174, 190, 190, 123, 146, 66, 30, 234, 105, 20, 70, 210, 44,
+    ];
+    std::fs::write(dir.join("keys/id.json"), format!("{:?}", keypair)).unwrap();
+    std::fs::write(
+        dir.join("app.rec"),
+        r#"{ authority: keypair_pubkey("keys/id.json") }"#,
+    )
+    .unwrap();
+
+    let signer = Signer::from_rec_file(dir.join("app.rec")).unwrap();
+    assert_eq!(
+        signer.authority,
+        "GmaDrppBC7P5ARKV8g3djiwP89vz1jLK23V2GBjuAEGB"
+    );
+
+    // Strings are evaluated too, against the current directory.
+    assert!(Signer::from_rec_str(r#"{ authority: keypair_pubkey("keys/id.json") }"#).is_err());
+    let signer = Signer::from_rec_str(r#"{ authority: "${CARGO_PKG_NAME}" }"#).unwrap();
+    assert_eq!(signer.authority, "rec-macros");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}